@@ -0,0 +1,340 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow, bail};
+use provenance_mark::{ProvenanceMarkGenerator, ProvenanceMarkInfo};
+
+use crate::{
+    config::ChainConfig,
+    utils::{read_existing_directory_path, read_new_path},
+};
+
+/// How many of a chain's most recent marks keep a generator snapshot, and so
+/// how many `undo` can take back in turn.
+pub const UNDO_DEPTH: u32 = 10;
+
+/// A chain directory, as created by `new`: the chain's generator in
+/// `generator.json`, and each of its marks in `marks/mark-N.json`.
+///
+/// The generator may instead be kept elsewhere, as named by the `generator`
+/// setting in the directory's `chain.toml` or by a `--generator` option.
+///
+/// A mirror, as created by `mirror init`, is a chain directory with no
+/// generator. It holds a copy of another chain's published marks.
+#[derive(Debug, Clone)]
+pub struct ChainDir {
+    path: PathBuf,
+    config: ChainConfig,
+    generator: Option<PathBuf>,
+}
+
+impl ChainDir {
+    /// Open an existing chain directory, reading its `chain.toml` if it has
+    /// one.
+    pub fn open(path: &PathBuf) -> Result<Self> {
+        let path = read_existing_directory_path(path)?;
+        let config = ChainConfig::load(&path)?;
+        Ok(Self {
+            path,
+            config,
+            generator: None,
+        })
+    }
+
+    /// Create a new, empty chain directory with its `marks` subdirectory. The
+    /// path must not already exist, but its parent must.
+    pub fn create(path: &PathBuf) -> Result<Self> {
+        let chain_dir = Self::plan(path)?;
+        fs::create_dir(&chain_dir.path)?;
+        fs::create_dir(chain_dir.marks_path())?;
+        Ok(chain_dir)
+    }
+
+    /// The chain directory that `create` would make at `path`, checked but
+    /// not created, as for a dry run.
+    pub fn plan(path: &PathBuf) -> Result<Self> {
+        let path = read_new_path(path)?;
+
+        // Ensure the directory doesn't already exist.
+        if path.exists() {
+            bail!("Path already exists: {}", path.display());
+        }
+
+        // Ensure the parent directory exists.
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                bail!("Parent directory does not exist: {}", parent.display());
+            }
+        } else {
+            bail!("Path has no parent directory: {}", path.display());
+        }
+
+        Ok(Self {
+            path,
+            config: ChainConfig::default(),
+            generator: None,
+        })
+    }
+
+    /// Use the generator at `path`, if given, instead of the one named by
+    /// `chain.toml` or kept in `generator.json`. A relative path is relative
+    /// to the current directory.
+    pub fn with_generator(mut self, path: Option<&PathBuf>) -> Result<Self> {
+        if let Some(path) = path {
+            self.generator = Some(read_new_path(path)?);
+        }
+        Ok(self)
+    }
+
+    /// Record in `chain.toml` that the chain's generator is kept at `path`.
+    pub fn set_generator_location(&mut self, path: PathBuf) -> Result<()> {
+        self.config.generator = Some(path);
+        self.config.save(&self.path)
+    }
+
+    /// Record in `chain.toml` that the chain succeeds the one at `path`.
+    pub fn set_predecessor(&mut self, path: PathBuf) -> Result<()> {
+        self.config.predecessor = Some(path);
+        self.config.save(&self.path)
+    }
+
+    /// The directory of the chain this chain succeeds, if it records one.
+    pub fn predecessor_path(&self) -> Option<PathBuf> {
+        self.config
+            .predecessor
+            .as_ref()
+            .map(|path| self.path.join(path))
+    }
+
+    pub fn config(&self) -> &ChainConfig {
+        &self.config
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where the chain's generator is kept.
+    pub fn generator_path(&self) -> PathBuf {
+        match self.generator.as_ref().or(self.config.generator.as_ref()) {
+            Some(path) => self.path.join(path),
+            None => self.path.join("generator.json"),
+        }
+    }
+
+    pub fn marks_path(&self) -> PathBuf {
+        self.path.join("marks")
+    }
+
+    pub fn mark_path(&self, seq: u32) -> PathBuf {
+        self.marks_path().join(format!("mark-{}.json", seq))
+    }
+
+    pub fn read_generator(&self) -> Result<ProvenanceMarkGenerator> {
+        Ok(self.read_generator_file()?.0)
+    }
+
+    /// Whether the chain has been closed by `close`, so that no more marks
+    /// may be generated.
+    pub fn is_closed(&self) -> Result<bool> {
+        Ok(self.read_generator_file()?.1)
+    }
+
+    fn read_generator_file(&self) -> Result<(ProvenanceMarkGenerator, bool)> {
+        let generator_path = self.generator_path();
+        let generator_json =
+            fs::read_to_string(&generator_path).map_err(|e| {
+                anyhow!(
+                    "Failed to read generator from {}: {}",
+                    generator_path.display(),
+                    e
+                )
+            })?;
+        parse_generator(&generator_json)
+    }
+
+    pub fn write_generator(
+        &self,
+        generator: &ProvenanceMarkGenerator,
+    ) -> Result<()> {
+        let generator_json = serde_json::to_string_pretty(generator)?;
+        fs::write(self.generator_path(), generator_json)?;
+        Ok(())
+    }
+
+    /// Write the generator back, flagged so that the chain is closed.
+    pub fn write_closed_generator(
+        &self,
+        generator: &ProvenanceMarkGenerator,
+    ) -> Result<()> {
+        let mut generator_json = serde_json::to_value(generator)?;
+        generator_json[CLOSED_KEY] = true.into();
+        fs::write(
+            self.generator_path(),
+            serde_json::to_string_pretty(&generator_json)?,
+        )?;
+        // No mark of a closed chain can be undone.
+        let head = generator.next_seq() - 1;
+        self.prune_snapshots(head, Some(head))
+    }
+
+    /// Where `next` keeps a copy of the generator's state before each mark
+    /// it generates, beside the generator itself: `generator-history` for
+    /// `generator.json`.
+    pub fn snapshots_path(&self) -> PathBuf {
+        let generator_path = self.generator_path();
+        let stem = generator_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "generator".to_string());
+        generator_path.with_file_name(format!("{}-history", stem))
+    }
+
+    fn snapshot_path(&self, seq: u32) -> PathBuf {
+        self.snapshots_path()
+            .join(format!("before-mark-{}.json", seq))
+    }
+
+    /// Save the generator's state before it generates its next mark, so that
+    /// `undo` can restore it.
+    pub fn save_snapshot(
+        &self,
+        generator: &ProvenanceMarkGenerator,
+    ) -> Result<()> {
+        fs::create_dir_all(self.snapshots_path())?;
+        let generator_json = serde_json::to_string_pretty(generator)?;
+        fs::write(self.snapshot_path(generator.next_seq()), generator_json)?;
+        Ok(())
+    }
+
+    /// The generator's state before it generated mark `seq`.
+    pub fn read_snapshot(&self, seq: u32) -> Result<ProvenanceMarkGenerator> {
+        let snapshot_path = self.snapshot_path(seq);
+        let generator_json =
+            fs::read_to_string(&snapshot_path).map_err(|e| {
+                anyhow!(
+                    "No generator snapshot from before mark {} at {}: {}",
+                    seq,
+                    snapshot_path.display(),
+                    e
+                )
+            })?;
+        Ok(serde_json::from_str(&generator_json)?)
+    }
+
+    pub fn remove_snapshot(&self, seq: u32) -> Result<()> {
+        fs::remove_file(self.snapshot_path(seq))?;
+        Ok(())
+    }
+
+    /// Delete the snapshots that `undo` can no longer use, given the chain's
+    /// `head` and the last mark that cannot be undone, such as a published
+    /// one: snapshots from before marks more than `UNDO_DEPTH` behind the
+    /// head, and from before that mark or any mark preceding it.
+    pub fn prune_snapshots(&self, head: u32, fixed: Option<u32>) -> Result<()> {
+        let snapshots_path = self.snapshots_path();
+        if !snapshots_path.is_dir() {
+            return Ok(());
+        }
+        let oldest = head
+            .saturating_sub(UNDO_DEPTH - 1)
+            .max(fixed.map_or(0, |seq| seq + 1));
+        for entry in fs::read_dir(&snapshots_path)? {
+            let entry = entry?;
+            let seq = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("before-mark-"))
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(|digits| digits.parse::<u32>().ok());
+            if seq.is_some_and(|seq| seq < oldest) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_mark(&self, seq: u32) -> Result<ProvenanceMarkInfo> {
+        let mark_path = self.mark_path(seq);
+        let mark_json = fs::read_to_string(&mark_path).map_err(|e| {
+            anyhow!(
+                "Failed to read mark {} from {}: {}",
+                seq,
+                mark_path.display(),
+                e
+            )
+        })?;
+        Ok(serde_json::from_str(&mark_json)?)
+    }
+
+    /// Write a mark as `marks/mark-N.json`, returning the path written.
+    pub fn write_mark(
+        &self,
+        mark_info: &ProvenanceMarkInfo,
+    ) -> Result<PathBuf> {
+        let mark_json = serde_json::to_string_pretty(mark_info)?;
+        let mark_path = self.mark_path(mark_info.mark().seq());
+        fs::write(&mark_path, mark_json)?;
+        Ok(mark_path)
+    }
+
+    pub fn remove_mark(&self, seq: u32) -> Result<()> {
+        fs::remove_file(self.mark_path(seq))?;
+        Ok(())
+    }
+
+    /// The sequence numbers of the mark files in `marks`, in order. Files not
+    /// named like `mark-N.json` are ignored.
+    pub fn mark_seqs(&self) -> Result<Vec<u32>> {
+        let mut seqs = Vec::new();
+        for entry in fs::read_dir(self.marks_path())? {
+            let name = entry?.file_name();
+            let seq = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("mark-"))
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(|digits| digits.parse::<u32>().ok());
+            seqs.extend(seq);
+        }
+        seqs.sort();
+        Ok(seqs)
+    }
+
+    /// Whether this is a mirror, which has no generator.
+    pub fn is_mirror(&self) -> bool {
+        self.generator.is_none()
+            && self.config.generator.is_none()
+            && !self.generator_path().exists()
+    }
+
+    /// The sequence number of the chain's last mark. When the generator can
+    /// be read this is the last mark generated; otherwise, as for a mirror or
+    /// a generator kept on a volume that is not mounted, it is the highest
+    /// mark the directory holds.
+    pub fn last_seq(&self) -> Result<u32> {
+        if !self.generator_path().exists() {
+            self.mark_seqs()?.last().copied().ok_or_else(|| {
+                anyhow!("No marks found in: {}", self.marks_path().display())
+            })
+        } else {
+            Ok(self.read_generator()?.next_seq() - 1)
+        }
+    }
+}
+
+/// The key added beside the generator's own fields in its JSON once the chain
+/// is closed.
+const CLOSED_KEY: &str = "closed";
+
+/// Parse a generator's JSON, along with whether the chain is closed.
+pub fn parse_generator(json: &str) -> Result<(ProvenanceMarkGenerator, bool)> {
+    let mut value: serde_json::Value = serde_json::from_str(json)?;
+    let closed = value
+        .as_object_mut()
+        .and_then(|object| object.remove(CLOSED_KEY))
+        .and_then(|closed| closed.as_bool())
+        .unwrap_or(false);
+    Ok((serde_json::from_value(value)?, closed))
+}
@@ -0,0 +1,56 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{Result, bail};
+use clap::Args;
+
+use crate::config::{CHAIN_ENV_VAR, UserConfig};
+
+/// Shared arguments for naming the chain a command works on.
+#[derive(Debug, Args, Default)]
+pub struct ChainArgs {
+    /// Path to the chain's directory. If not supplied, the chain named by
+    /// `--chain` or the `PROVENANCE_CHAIN` environment variable is used.
+    pub path: Option<PathBuf>,
+
+    /// The name of a chain listed in the user's `config.toml`.
+    #[arg(long, value_name = "NAME", conflicts_with = "path")]
+    pub chain: Option<String>,
+}
+
+impl ChainArgs {
+    /// The chain directory's path, from the command line or, failing that,
+    /// the `PROVENANCE_CHAIN` environment variable.
+    pub fn path(&self, user: &UserConfig) -> Result<PathBuf> {
+        if let Some(path) = &self.path {
+            return Ok(path.clone());
+        }
+        if let Some(name) = &self.chain {
+            return user.chain_path(name);
+        }
+        match env::var(CHAIN_ENV_VAR) {
+            Ok(name) if !name.is_empty() => user.chain_path(&name),
+            _ => bail!(
+                "No chain given; supply a path, `--chain <NAME>`, or set {}",
+                CHAIN_ENV_VAR
+            ),
+        }
+    }
+}
+
+/// The shared argument for finding a chain's generator when it is kept
+/// outside the chain's directory.
+#[derive(Debug, Args, Default)]
+pub struct GeneratorArgs {
+    /// Path to the chain's generator, if it is kept somewhere other than
+    /// where the chain's `chain.toml` says, or `generator.json` in the chain's
+    /// directory.
+    #[arg(long, value_name = "PATH")]
+    pub generator: Option<PathBuf>,
+}
+
+impl GeneratorArgs {
+    /// The generator's path, if one was given.
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.generator.as_ref()
+    }
+}
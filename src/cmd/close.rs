@@ -0,0 +1,154 @@
+use anyhow::{Result, bail};
+use bc_envelope::prelude::*;
+use clap::Args;
+use provenance_mark::{ProvenanceMark, ProvenanceMarkInfo, util::parse_date};
+
+use super::{
+    chain::{ChainArgs, GeneratorArgs},
+    date_check::DateCheckArgs,
+    print::OutputFormat,
+    succession::Successor,
+};
+use crate::{
+    chain_dir::ChainDir,
+    config::{UserConfig, default_format},
+};
+
+/// The subject of the envelope that a closing mark holds in its `info`.
+pub const CLOSURE_SUBJECT: &str = "ProvenanceChainClosed";
+
+/// Close a chain with a final mark, after which `next` refuses to run.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    #[command(flatten)]
+    generator: GeneratorArgs,
+
+    /// Why the chain is being closed, such as the project being retired or
+    /// the seed being suspected compromised. Stored in the closing mark.
+    #[arg(long)]
+    reason: String,
+
+    /// A comment to be included for the closing mark. (Comments are not part
+    /// of the mark itself.)
+    #[arg(short, long, default_value = "Chain closed.")]
+    comment: String,
+
+    /// The date of the closing mark. If not supplied, the current date is
+    /// used.
+    #[arg(short, long, value_parser = parse_date)]
+    date: Option<Date>,
+
+    #[command(flatten)]
+    dates: DateCheckArgs,
+
+    /// Suppress informational status output on stderr/stdout.
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Output format for the closing mark. If not supplied, the default from
+    /// the chain's `chain.toml` or the user's `config.toml` is used, or else
+    /// `markdown`.
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let user = UserConfig::load()?;
+        let chain_dir = ChainDir::open(&self.chain.path(&user)?)?
+            .with_generator(self.generator.path())?;
+        if chain_dir.is_closed()? {
+            bail!("The chain is already closed.");
+        }
+        let mut generator = chain_dir.read_generator()?;
+
+        // Generate the closing mark, whose `info` states that the chain ends.
+        // Its date is checked against the head like any other mark's.
+        let head = chain_dir.read_mark(generator.next_seq() - 1)?;
+        let date = self.dates.check(
+            *generator.res(),
+            self.date.unwrap_or_else(Date::now),
+            Some(head.mark().date()),
+        )?;
+        let closure = ChainClosure {
+            reason: self.reason.clone(),
+            successor: None,
+        };
+        let mark = generator.next(date, Some(closure.to_cbor()));
+        let mark_info =
+            ProvenanceMarkInfo::new(mark.clone(), self.comment.clone());
+        let mark_path = chain_dir.write_mark(&mark_info)?;
+        chain_dir.write_closed_generator(&generator)?;
+
+        let status_line = format!(
+            "Chain closed with mark {}, written to: {} (dated {})",
+            mark.seq(),
+            mark_path.display(),
+            mark.date()
+        );
+        let format = match self.format {
+            Some(format) => format,
+            None => default_format(chain_dir.config(), &user)?
+                .unwrap_or(OutputFormat::Markdown),
+        };
+        match format {
+            OutputFormat::Markdown => {
+                let mut paragraphs: Vec<String> = Vec::new();
+                if !self.quiet {
+                    paragraphs.push(status_line);
+                }
+                paragraphs.push(mark_info.markdown_summary());
+                Ok(paragraphs.join("\n\n"))
+            }
+            OutputFormat::Ur => {
+                if !self.quiet {
+                    eprintln!("{}", status_line);
+                }
+                Ok(mark_info.ur().to_string())
+            }
+            OutputFormat::Json => {
+                if !self.quiet {
+                    eprintln!("{}", status_line);
+                }
+                serde_json::to_string_pretty(&mark_info).map_err(Into::into)
+            }
+        }
+    }
+}
+
+/// The statement in a closing mark's `info` that its chain ends there: an
+/// envelope whose subject is `ProvenanceChainClosed`, with the reason as a
+/// `reason` assertion. A chain closed by `new --succeeds` also commits to the
+/// genesis mark of the chain that succeeds it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainClosure {
+    pub reason: String,
+    pub successor: Option<Successor>,
+}
+
+impl ChainClosure {
+    pub fn to_cbor(&self) -> CBOR {
+        let envelope = Envelope::new(CLOSURE_SUBJECT)
+            .add_assertion("reason", self.reason.clone());
+        match &self.successor {
+            Some(successor) => successor.add_to(envelope),
+            None => envelope,
+        }
+        .tagged_cbor()
+    }
+
+    /// The closing statement in a mark's `info`, if it holds one.
+    pub fn from_mark(mark: &ProvenanceMark) -> Option<Self> {
+        let envelope = Envelope::try_from(mark.info()?).ok()?;
+        if envelope.extract_subject::<String>().ok()? != CLOSURE_SUBJECT {
+            return None;
+        }
+        let reason = envelope.extract_object_for_predicate("reason").ok()?;
+        let successor = Successor::from_envelope(&envelope);
+        Some(Self { reason, successor })
+    }
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Args;
+use dcbor::prelude::*;
+use provenance_mark::ProvenanceMarkResolution;
+
+use super::new::Resolution;
+use crate::utils::{format_duration, parse_duration};
+
+/// Safeguards against a mistyped mark date.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct DateCheckArgs {
+    /// How far past the current time a mark may be dated.
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        default_value = "1h"
+    )]
+    max_skew: Duration,
+
+    /// Generate the mark even if it is dated before the chain's last mark or
+    /// more than `--max-skew` past the current time.
+    #[arg(long)]
+    force: bool,
+}
+
+impl DateCheckArgs {
+    /// The date a mark will be stored with, once rounded to what the chain's
+    /// resolution can encode. Unless `--force` is given, fails if that date
+    /// is before `previous`, the date of the chain's last mark, or too far in
+    /// the future. A date the resolution cannot encode at all is always an
+    /// error.
+    pub fn check(
+        &self,
+        res: ProvenanceMarkResolution,
+        date: Date,
+        previous: Option<Date>,
+    ) -> Result<Date> {
+        let resolution = Resolution::from_provenance_mark_resolution(res);
+        let stored = res
+            .serialize_date(date)
+            .and_then(|data| res.deserialize_date(&data))
+            .map_err(|e| {
+                anyhow!(
+                    "The date {} cannot be encoded at {} resolution: {}",
+                    date,
+                    resolution,
+                    e
+                )
+            })?;
+        if self.force {
+            return Ok(stored);
+        }
+        if let Some(previous) = previous
+            && stored < previous
+        {
+            bail!(
+                "The date {} is before the chain's last mark, dated {}. Use \
+                 --force to generate the mark anyway.",
+                stored,
+                previous
+            );
+        }
+        let ahead = stored.timestamp() - Date::now().timestamp();
+        if ahead > self.max_skew.as_secs_f64() {
+            bail!(
+                "The date {} is {} in the future, more than --max-skew ({}). \
+                 Use --force to generate the mark anyway.",
+                stored,
+                format_duration(ahead),
+                format_duration(self.max_skew.as_secs_f64())
+            );
+        }
+        Ok(stored)
+    }
+}
@@ -0,0 +1,277 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Result, bail};
+use clap::{Args, ValueEnum};
+use provenance_mark::ProvenanceMark;
+
+use crate::{
+    chain_dir::ChainDir,
+    cmd::{chain::ChainArgs, proof},
+    config::UserConfig,
+    exec::ExitError,
+};
+
+/// Compare two copies of a chain, such as a primary chain directory and its
+/// mirror.
+///
+/// Exits with status 1 if the copies differ.
+#[derive(Debug, Args)]
+#[group(skip)]
+#[command(allow_missing_positional = true)]
+pub struct CommandArgs {
+    /// The first copy: a chain directory, a file holding a proof bundle, or a
+    /// proof bundle itself. If not supplied, the chain named by `--chain` or
+    /// the `PROVENANCE_CHAIN` environment variable is used.
+    a: Option<String>,
+
+    /// The second copy, in any of the same forms as A.
+    b: String,
+
+    /// The name of a chain listed in the user's `config.toml`, to be used as
+    /// the first copy.
+    #[arg(long, value_name = "NAME", conflicts_with = "a")]
+    chain: Option<String>,
+
+    /// Output format for the differences.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let a = match &self.a {
+            Some(a) => read_copy(a)?,
+            None => {
+                let chain = ChainArgs {
+                    path: None,
+                    chain: self.chain.clone(),
+                };
+                read_copy(&chain.path(&UserConfig::load()?)?.to_string_lossy())?
+            }
+        };
+        let b = read_copy(&self.b)?;
+
+        // Marks of unrelated chains share sequence numbers by chance, which
+        // would otherwise be listed as forks.
+        if let (Some(chain_a), Some(chain_b)) = (chain_id(&a), chain_id(&b))
+            && chain_a != chain_b
+        {
+            bail!(
+                "The copies are of different chains: A is chain {}, B is \
+                 chain {}",
+                hex::encode(chain_a),
+                hex::encode(chain_b)
+            );
+        }
+
+        let diff = Diff::between(&a, &b);
+        let output = match self.format {
+            Format::Text => diff.to_text(),
+            Format::Json => serde_json::to_string_pretty(&diff.to_json())?,
+        };
+        if !diff.is_empty() {
+            return Err(ExitError::new(1, "The copies differ")
+                .with_output(output)
+                .into());
+        }
+        Ok(output)
+    }
+}
+
+/// A mark in one copy of a chain, with its comment if the copy keeps one.
+/// Proof bundles hold marks without comments.
+#[derive(Debug)]
+struct Entry {
+    mark: ProvenanceMark,
+    comment: Option<String>,
+}
+
+type ChainCopy = BTreeMap<u32, Entry>;
+
+fn read_copy(arg: &str) -> Result<ChainCopy> {
+    let path = Path::new(arg);
+    let entries: Vec<Entry> = if path.is_dir() {
+        let chain_dir = ChainDir::open(&path.to_path_buf())?;
+        chain_dir
+            .mark_seqs()?
+            .into_iter()
+            .map(|seq| {
+                let mark_info = chain_dir.read_mark(seq)?;
+                Ok(Entry {
+                    mark: mark_info.mark().clone(),
+                    comment: Some(mark_info.comment().to_string()),
+                })
+            })
+            .collect::<Result<_>>()?
+    } else {
+        let bundle = if path.is_file() {
+            fs::read_to_string(path)?
+        } else {
+            arg.to_string()
+        };
+        proof::read_bundle(bundle.trim())?
+            .into_iter()
+            .map(|mark| Entry {
+                mark,
+                comment: None,
+            })
+            .collect()
+    };
+
+    if let Some(first) = entries.first()
+        && entries
+            .iter()
+            .any(|entry| entry.mark.chain_id() != first.mark.chain_id())
+    {
+        bail!("{} holds marks of more than one chain", arg);
+    }
+
+    let mut copy = ChainCopy::new();
+    for entry in entries {
+        let seq = entry.mark.seq();
+        if copy.insert(seq, entry).is_some() {
+            bail!("{} holds more than one mark {}", arg, seq);
+        }
+    }
+    Ok(copy)
+}
+
+/// The ID of the chain a copy holds, if it holds any marks.
+fn chain_id(copy: &ChainCopy) -> Option<&[u8]> {
+    copy.values().next().map(|entry| entry.mark.chain_id())
+}
+
+/// The differences between two copies of a chain.
+#[derive(Debug, Default)]
+struct Diff {
+    only_in_a: Vec<ProvenanceMark>,
+    only_in_b: Vec<ProvenanceMark>,
+    /// Identical marks whose comments differ.
+    comments: Vec<(ProvenanceMark, String, String)>,
+    /// Different marks with the same sequence number.
+    forks: Vec<(ProvenanceMark, ProvenanceMark)>,
+    identical: usize,
+}
+
+impl Diff {
+    fn between(a: &ChainCopy, b: &ChainCopy) -> Self {
+        let mut diff = Self::default();
+        for (seq, entry_a) in a {
+            let Some(entry_b) = b.get(seq) else {
+                diff.only_in_a.push(entry_a.mark.clone());
+                continue;
+            };
+            if entry_a.mark != entry_b.mark {
+                diff.forks
+                    .push((entry_a.mark.clone(), entry_b.mark.clone()));
+                continue;
+            }
+            diff.identical += 1;
+            if let (Some(comment_a), Some(comment_b)) =
+                (&entry_a.comment, &entry_b.comment)
+                && comment_a != comment_b
+            {
+                diff.comments.push((
+                    entry_a.mark.clone(),
+                    comment_a.clone(),
+                    comment_b.clone(),
+                ));
+            }
+        }
+        diff.only_in_b = b
+            .iter()
+            .filter(|(seq, _)| !a.contains_key(seq))
+            .map(|(_, entry)| entry.mark.clone())
+            .collect();
+        diff
+    }
+
+    fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty()
+            && self.only_in_b.is_empty()
+            && self.comments.is_empty()
+            && self.forks.is_empty()
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = vec![format!("Identical marks: {}", self.identical)];
+        let mut section = |heading: &str, items: Vec<String>| {
+            if !items.is_empty() {
+                lines.push(String::new());
+                lines.push(format!("{}:", heading));
+                lines.extend(
+                    items.into_iter().map(|item| format!("  {}", item)),
+                );
+            }
+        };
+        section("Only in A", self.only_in_a.iter().map(describe).collect());
+        section("Only in B", self.only_in_b.iter().map(describe).collect());
+        section(
+            "Comments differ",
+            self.comments
+                .iter()
+                .map(|(mark, a, b)| {
+                    format!("{}: A {:?}, B {:?}", describe(mark), a, b)
+                })
+                .collect(),
+        );
+        section(
+            "Forks (same sequence number, different marks)",
+            self.forks
+                .iter()
+                .map(|(a, b)| {
+                    format!(
+                        "{}: A {}, B {}",
+                        a.seq(),
+                        a.identifier(),
+                        b.identifier()
+                    )
+                })
+                .collect(),
+        );
+        if self.is_empty() {
+            lines.push("The copies are identical.".to_string());
+        }
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "identical": self.identical,
+            "onlyInA": self.only_in_a.iter().map(mark_json).collect::<Vec<_>>(),
+            "onlyInB": self.only_in_b.iter().map(mark_json).collect::<Vec<_>>(),
+            "comments": self.comments.iter().map(|(mark, a, b)| {
+                serde_json::json!({
+                    "seq": mark.seq(),
+                    "identifier": mark.identifier(),
+                    "a": a,
+                    "b": b,
+                })
+            }).collect::<Vec<_>>(),
+            "forks": self.forks.iter().map(|(a, b)| {
+                serde_json::json!({
+                    "seq": a.seq(),
+                    "a": a.identifier(),
+                    "b": b.identifier(),
+                })
+            }).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn describe(mark: &ProvenanceMark) -> String {
+    format!("{}: {}", mark.seq(), mark.identifier())
+}
+
+fn mark_json(mark: &ProvenanceMark) -> serde_json::Value {
+    serde_json::json!({
+        "seq": mark.seq(),
+        "identifier": mark.identifier(),
+    })
+}
@@ -0,0 +1,102 @@
+use anyhow::{Result, bail};
+use bc_envelope::prelude::*;
+use bc_ur::UR;
+use provenance_mark::ProvenanceMark;
+
+/// Extract a ProvenanceMark from a UR string.
+///
+/// Supports three types of URs:
+/// 1. `ur:provenance` - Direct provenance mark
+/// 2. `ur:envelope` - Envelope with a 'provenance' assertion
+/// 3. Any other UR type - Attempts to decode CBOR as an envelope
+pub fn provenance_mark_from_ur(ur_string: &str) -> Result<ProvenanceMark> {
+    // Parse the UR to get its type and CBOR
+    let ur = UR::from_ur_string(ur_string).map_err(|e| {
+        anyhow::anyhow!("Failed to parse UR '{}': {}", ur_string, e)
+    })?;
+
+    let ur_type = ur.ur_type_str();
+    let cbor = ur.cbor();
+
+    // Case 1: Direct provenance mark
+    // URs don't include the CBOR tag in their encoded format, so we use
+    // from_untagged_cbor
+    if ur_type == "provenance" {
+        return ProvenanceMark::from_untagged_cbor(cbor).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to decode provenance mark from '{}': {}",
+                ur_string,
+                e
+            )
+        });
+    }
+
+    // Case 2 & 3: Try to decode CBOR as an envelope
+    let envelope = Envelope::from_untagged_cbor(cbor.clone()).map_err(|e| {
+        anyhow::anyhow!(
+            "UR type '{}' is not 'provenance', and CBOR is not decodable as an envelope: {}",
+            ur_type,
+            e
+        )
+    })?;
+
+    // Extract the provenance mark from the envelope
+    provenance_mark_from_envelope(&envelope, ur_string)
+}
+
+/// Extract a ProvenanceMark from an Envelope.
+///
+/// The envelope must contain exactly one 'provenance' assertion (possibly
+/// inside one or more wrapper layers), and the object subject of that
+/// assertion must be a ProvenanceMark.
+pub fn provenance_mark_from_envelope(
+    envelope: &Envelope,
+    ur_string: &str,
+) -> Result<ProvenanceMark> {
+    let mut current = envelope.clone();
+
+    loop {
+        // Find all assertions with the 'provenance' predicate at this
+        // level.
+        let provenance_assertions =
+            current.assertions_with_predicate(known_values::PROVENANCE);
+
+        // Verify exactly one provenance assertion exists.
+        if provenance_assertions.len() > 1 {
+            bail!(
+                "Envelope in '{}' contains {} 'provenance' assertions, expected exactly one",
+                ur_string,
+                provenance_assertions.len()
+            );
+        }
+        if let Some(provenance_assertion) = provenance_assertions.first() {
+            // Get the object of the provenance assertion.
+            let object_envelope = provenance_assertion.as_object()
+                .ok_or_else(|| anyhow::anyhow!(
+                    "Failed to extract object from provenance assertion in '{}'",
+                    ur_string
+                ))?;
+
+            // The object should be decodable as a ProvenanceMark.
+            // ProvenanceMark::try_from(Envelope) will extract the subject
+            // and decode it.
+            return ProvenanceMark::try_from(object_envelope).map_err(|e| anyhow::anyhow!(
+                "Failed to decode ProvenanceMark from provenance assertion in '{}': {}",
+                ur_string,
+                e
+            ));
+        }
+
+        // No provenance at this level. If this is a wrapper around a
+        // signed/content envelope, strip one layer and try again.
+        match current.try_unwrap() {
+            Ok(unwrapped) => current = unwrapped,
+            Err(_) => {
+                bail!(
+                    "Envelope in '{}' does not contain a 'provenance' assertion",
+                    ur_string
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use provenance_mark::ProvenanceMark;
+
+use crate::{cmd::extract, exec::ExitError};
+
+/// Check whether one mark is the direct successor of another.
+///
+/// Exits with status 1 unless B directly follows A.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    /// The earlier mark. Accepts the same UR forms as `validate`.
+    a: String,
+
+    /// The mark that should follow A. Accepts the same UR forms as
+    /// `validate`.
+    b: String,
+
+    /// Output format for the result.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let a = extract::provenance_mark_from_ur(self.a.trim())?;
+        let b = extract::provenance_mark_from_ur(self.b.trim())?;
+        let relation = Relation::between(&a, &b);
+        let output = match self.format {
+            Format::Text => relation.to_text(&a, &b),
+            Format::Json => {
+                serde_json::to_string_pretty(&relation.to_json(&a, &b))?
+            }
+        };
+        if !relation.direct_successor {
+            return Err(ExitError::new(
+                1,
+                "B is not the direct successor of A",
+            )
+            .with_output(output)
+            .into());
+        }
+        Ok(output)
+    }
+}
+
+/// How mark B stands in relation to mark A.
+#[derive(Debug)]
+struct Relation {
+    same_chain: bool,
+    /// Whether B's key and A's hash link B to A as its successor.
+    direct_successor: bool,
+    /// The number of marks between A and B, when B comes later in the same
+    /// chain.
+    marks_between: Option<u32>,
+    /// Whether the marks' dates are in the same order as their sequence
+    /// numbers, when they belong to the same chain.
+    dates_consistent: Option<bool>,
+    /// Why B is not A's direct successor.
+    problem: Option<String>,
+}
+
+impl Relation {
+    fn between(a: &ProvenanceMark, b: &ProvenanceMark) -> Self {
+        let same_chain = a.chain_id() == b.chain_id();
+        let marks_between =
+            (same_chain && b.seq() > a.seq()).then(|| b.seq() - a.seq() - 1);
+        // A mark's date may equal its predecessor's, but never precede it.
+        // The dates of unrelated chains say nothing about each other.
+        let dates_consistent =
+            same_chain.then(|| match a.seq().cmp(&b.seq()) {
+                std::cmp::Ordering::Less => a.date() <= b.date(),
+                std::cmp::Ordering::Equal => a.date() == b.date(),
+                std::cmp::Ordering::Greater => a.date() >= b.date(),
+            });
+        let problem = if !same_chain {
+            Some("the marks belong to different chains".to_string())
+        } else if b.seq() <= a.seq() {
+            Some(format!(
+                "mark {} does not come after mark {}",
+                b.seq(),
+                a.seq()
+            ))
+        } else if let Some(n) = marks_between.filter(|n| *n > 0) {
+            Some(format!(
+                "{} {} between them; their linkage cannot be checked without \
+                 the marks in between",
+                n,
+                if n == 1 { "mark lies" } else { "marks lie" }
+            ))
+        } else {
+            a.precedes_opt(b).err().map(|e| e.to_string())
+        };
+        Self {
+            same_chain,
+            direct_successor: problem.is_none(),
+            marks_between,
+            dates_consistent,
+            problem,
+        }
+    }
+
+    fn to_text(&self, a: &ProvenanceMark, b: &ProvenanceMark) -> String {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        let mut lines = vec![
+            format!("A: {}", describe(a)),
+            format!("B: {}", describe(b)),
+            format!("Same chain: {}", yes_no(self.same_chain)),
+        ];
+        lines.push(match &self.problem {
+            None => "Direct successor: yes".to_string(),
+            Some(problem) => format!("Direct successor: no ({})", problem),
+        });
+        if let Some(n) = self.marks_between {
+            lines.push(format!("Marks in between: {}", n));
+        }
+        lines.push(format!(
+            "Dates consistent: {} ({} to {})",
+            self.dates_consistent.map_or("n/a", yes_no),
+            a.date(),
+            b.date()
+        ));
+        lines.join("\n")
+    }
+
+    fn to_json(
+        &self,
+        a: &ProvenanceMark,
+        b: &ProvenanceMark,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "a": mark_json(a),
+            "b": mark_json(b),
+            "sameChain": self.same_chain,
+            "directSuccessor": self.direct_successor,
+            "marksBetween": self.marks_between,
+            "datesConsistent": self.dates_consistent,
+            "problem": self.problem,
+        })
+    }
+}
+
+fn describe(mark: &ProvenanceMark) -> String {
+    format!(
+        "mark {} of chain {} ({})",
+        mark.seq(),
+        hex::encode(mark.chain_id()),
+        mark.bytewords_identifier(true)
+    )
+}
+
+fn mark_json(mark: &ProvenanceMark) -> serde_json::Value {
+    serde_json::json!({
+        "chain": hex::encode(mark.chain_id()),
+        "seq": mark.seq(),
+        "date": mark.date().to_string(),
+        "identifier": mark.identifier(),
+    })
+}
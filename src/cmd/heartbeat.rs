@@ -0,0 +1,20 @@
+use bc_envelope::prelude::*;
+use provenance_mark::ProvenanceMark;
+
+/// The subject of the envelope that a heartbeat mark holds in its `info`.
+pub const HEARTBEAT_SUBJECT: &str = "ProvenanceHeartbeat";
+
+/// The `info` of a heartbeat mark: an envelope whose subject is
+/// `ProvenanceHeartbeat`. A heartbeat mark attests to no content; it only
+/// shows that whoever holds the chain's generator still controls it.
+pub fn heartbeat_cbor() -> CBOR {
+    Envelope::new(HEARTBEAT_SUBJECT).tagged_cbor()
+}
+
+/// Whether a mark's `info` marks it as a heartbeat.
+pub fn is_heartbeat(mark: &ProvenanceMark) -> bool {
+    mark.info()
+        .and_then(|info| Envelope::try_from(info).ok())
+        .and_then(|envelope| envelope.extract_subject::<String>().ok())
+        .is_some_and(|subject| subject == HEARTBEAT_SUBJECT)
+}
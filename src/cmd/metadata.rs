@@ -0,0 +1,110 @@
+use anyhow::Result;
+use bc_envelope::prelude::*;
+use clap::Args;
+use provenance_mark::ProvenanceMark;
+use serde::Serialize;
+
+/// The subject of the envelope that holds a chain's metadata in its genesis
+/// mark's `info`.
+pub const METADATA_SUBJECT: &str = "ProvenanceChainMetadata";
+
+/// Shared arguments for describing a chain when it is created.
+#[derive(Debug, Args, Default)]
+pub struct MetadataArgs {
+    /// A human-readable name for the chain.
+    #[arg(long, conflicts_with = "info")]
+    pub name: Option<String>,
+
+    /// The person or organization that owns the chain.
+    #[arg(long, conflicts_with = "info")]
+    pub owner: Option<String>,
+
+    /// What the chain is for.
+    #[arg(long, conflicts_with = "info")]
+    pub description: Option<String>,
+
+    /// Where to find out more about the chain or contact its owner.
+    #[arg(long, conflicts_with = "info")]
+    pub url: Option<String>,
+}
+
+impl MetadataArgs {
+    /// The metadata given, if any.
+    pub fn to_metadata(&self) -> Option<ChainMetadata> {
+        let metadata = ChainMetadata {
+            name: self.name.clone(),
+            owner: self.owner.clone(),
+            description: self.description.clone(),
+            url: self.url.clone(),
+        };
+        (metadata != ChainMetadata::default()).then_some(metadata)
+    }
+}
+
+/// A chain's name, owner, description, and URL, stored in its genesis mark so
+/// that they are bound to the chain.
+///
+/// The metadata is an envelope whose subject is `ProvenanceChainMetadata`,
+/// with a string assertion for each field given.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChainMetadata {
+    pub name: Option<String>,
+    pub owner: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+}
+
+impl ChainMetadata {
+    pub fn to_envelope(&self) -> Envelope {
+        Envelope::new(METADATA_SUBJECT)
+            .add_optional_assertion("name", self.name.clone())
+            .add_optional_assertion("owner", self.owner.clone())
+            .add_optional_assertion("description", self.description.clone())
+            .add_optional_assertion("url", self.url.clone())
+    }
+
+    /// The metadata as a mark's `info`.
+    pub fn to_cbor(&self) -> CBOR {
+        self.to_envelope().tagged_cbor()
+    }
+
+    /// The metadata in a genesis mark's `info`, if it holds any. A genesis
+    /// mark that only names its chain's predecessor holds none.
+    pub fn from_mark(mark: &ProvenanceMark) -> Option<Self> {
+        if !mark.is_genesis() {
+            return None;
+        }
+        let envelope = Envelope::try_from(mark.info()?).ok()?;
+        if envelope.extract_subject::<String>().ok()? != METADATA_SUBJECT {
+            return None;
+        }
+        Self::from_envelope(&envelope)
+            .ok()
+            .filter(|metadata| *metadata != Self::default())
+    }
+
+    fn from_envelope(envelope: &Envelope) -> Result<Self> {
+        Ok(Self {
+            name: envelope.extract_optional_object_for_predicate("name")?,
+            owner: envelope.extract_optional_object_for_predicate("owner")?,
+            description: envelope
+                .extract_optional_object_for_predicate("description")?,
+            url: envelope.extract_optional_object_for_predicate("url")?,
+        })
+    }
+
+    /// A `Field: value` line for each field that is set.
+    pub fn lines(&self) -> Vec<String> {
+        [
+            ("Name", &self.name),
+            ("Owner", &self.owner),
+            ("Description", &self.description),
+            ("URL", &self.url),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| {
+            value.as_ref().map(|value| format!("{}: {}", label, value))
+        })
+        .collect()
+    }
+}
@@ -0,0 +1,33 @@
+mod add;
+mod init;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::exec::Exec;
+
+/// Keep a verification-only copy of another party's chain.
+///
+/// A mirror is a chain directory with no generator. Marks are appended to it
+/// as they are published, and only if they extend the mirror's current head.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Init(init::CommandArgs),
+    Add(add::CommandArgs),
+}
+
+impl Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        match &self.command {
+            Commands::Init(args) => args.exec(),
+            Commands::Add(args) => args.exec(),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Args;
+use provenance_mark::ProvenanceMarkInfo;
+
+use crate::{
+    chain_dir::ChainDir,
+    cmd::{chain::ChainArgs, extract},
+    config::UserConfig,
+};
+
+/// Append published marks to a mirror.
+///
+/// The marks must extend the mirror's head in order. If any mark does not,
+/// none are added.
+#[derive(Debug, Args)]
+#[group(skip)]
+#[command(allow_missing_positional = true)]
+pub struct CommandArgs {
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    /// The marks to append, in sequence order. Accepts the same UR forms as
+    /// `validate`. Without a path before them, they follow `--`.
+    #[arg(required = true, value_name = "UR")]
+    marks: Vec<String>,
+
+    /// A comment to be stored with each mark. (Comments are not part of the
+    /// mark itself.)
+    #[arg(short, long, default_value = "")]
+    comment: String,
+
+    /// Suppress informational status output.
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let chain_dir =
+            ChainDir::open(&self.chain.path(&UserConfig::load()?)?)?;
+        if !chain_dir.is_mirror() {
+            bail!(
+                "{} has a generator; use `next` to add marks to it.",
+                chain_dir.path().display()
+            );
+        }
+        let mut head =
+            chain_dir.read_mark(chain_dir.last_seq()?)?.mark().clone();
+
+        // Check every mark before writing any, so that a bad mark leaves the
+        // mirror unchanged.
+        let mut mark_infos = Vec::new();
+        for ur in &self.marks {
+            let mark = extract::provenance_mark_from_ur(ur.trim())?;
+            head.precedes_opt(&mark).map_err(|e| {
+                anyhow!(
+                    "Mark {} does not extend the mirror's head (mark {}): {}",
+                    mark.seq(),
+                    head.seq(),
+                    e
+                )
+            })?;
+            head = mark.clone();
+            mark_infos
+                .push(ProvenanceMarkInfo::new(mark, self.comment.clone()));
+        }
+
+        let mut status_lines = Vec::new();
+        for mark_info in &mark_infos {
+            let mark_path = chain_dir.write_mark(mark_info)?;
+            status_lines.push(format!(
+                "Mark {} written to: {}",
+                mark_info.mark().seq(),
+                mark_path.display()
+            ));
+        }
+
+        if self.quiet {
+            return Ok(String::new());
+        }
+        Ok(status_lines.join("\n"))
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::{Result, bail};
+use clap::Args;
+use provenance_mark::ProvenanceMarkInfo;
+
+use crate::{
+    chain_dir::ChainDir,
+    cmd::{chain::ChainArgs, extract},
+    config::UserConfig,
+};
+
+/// Create a mirror from a chain's published genesis mark.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    /// The chain's genesis mark. Accepts the same UR forms as `validate`.
+    #[arg(short, long, value_name = "UR")]
+    genesis: String,
+
+    /// A comment to be stored with the genesis mark. (Comments are not part
+    /// of the mark itself.)
+    #[arg(short, long, default_value = "")]
+    comment: String,
+
+    /// Suppress informational status output.
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let mark = extract::provenance_mark_from_ur(self.genesis.trim())?;
+        if !mark.is_genesis() {
+            bail!("Mark {} is not a genesis mark.", mark.seq());
+        }
+
+        let path = self.chain.path(&UserConfig::load()?)?;
+        let chain_dir = ChainDir::create(&path)?;
+        let mark_path = chain_dir
+            .write_mark(&ProvenanceMarkInfo::new(mark, self.comment.clone()))?;
+
+        if self.quiet {
+            return Ok(String::new());
+        }
+        Ok(format!(
+            "Mirror created at: {}\nMark 0 written to: {}",
+            chain_dir.path().display(),
+            mark_path.display()
+        ))
+    }
+}
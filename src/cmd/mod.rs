@@ -1,6 +1,22 @@
+pub mod chain;
+pub mod close;
+pub mod date_check;
+pub mod diff;
+pub mod extract;
+pub mod follows;
+pub mod heartbeat;
 pub mod info;
+pub mod metadata;
+pub mod mirror;
 pub mod new;
 pub mod next;
+pub mod notice;
 pub mod print;
+pub mod proof;
+pub mod publish_record;
 pub mod seed;
+pub mod status;
+pub mod succession;
+pub mod undo;
 pub mod validate;
+pub mod verify_proof;
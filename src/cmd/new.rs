@@ -1,31 +1,63 @@
-use std::{fs, path::PathBuf};
+use std::{fmt, path::PathBuf};
 
 use anyhow::{Result, bail};
 use clap::{Args, ValueEnum};
 use dcbor::prelude::*;
 use provenance_mark::{
-    ProvenanceMarkGenerator, ProvenanceMarkInfo, ProvenanceMarkResolution,
-    ProvenanceSeed, util::parse_date,
+    ProvenanceMark, ProvenanceMarkGenerator, ProvenanceMarkInfo,
+    ProvenanceMarkResolution, ProvenanceSeed, util::parse_date,
 };
 
-use super::{info::InfoArgs, print::OutputFormat, seed};
-use crate::utils::read_new_path;
+use super::{
+    chain::ChainArgs,
+    close::ChainClosure,
+    date_check::DateCheckArgs,
+    info::InfoArgs,
+    metadata::MetadataArgs,
+    next::DRY_RUN_NOTE,
+    print::OutputFormat,
+    seed,
+    succession::{Predecessor, Successor},
+};
+use crate::{chain_dir::ChainDir, config::UserConfig, utils::read_new_path};
+
+/// Shown with the output of a dry run without `--seed`, whose genesis mark
+/// the real run cannot reproduce.
+const RANDOM_SEED_DRY_RUN_NOTE: &str = "Dry run: nothing was written. No \
+    seed was given, so the real run will draw a new random seed: its genesis \
+    mark's chain ID, key, and hash will differ from this preview.";
 
 /// Initialize a directory with a new provenance mark chain.
+///
+/// The directory must not already exist, but its parent must.
 #[derive(Debug, Args)]
 #[group(skip)]
 pub struct CommandArgs {
-    /// Path to directory to be created. Must not already exist.
-    path: PathBuf,
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    /// Path at which to store the chain's generator instead of
+    /// `generator.json` in the chain's directory, such as on an encrypted
+    /// volume. Must not already exist. The path is recorded in the chain's
+    /// `chain.toml`.
+    #[arg(long, value_name = "PATH")]
+    generator: Option<PathBuf>,
+
+    /// Path to the directory of a chain that the new chain replaces. The new
+    /// genesis mark names the old chain's head, and the old chain is closed
+    /// with a final mark that commits to the new genesis mark.
+    #[arg(long, value_name = "DIR", conflicts_with = "info")]
+    succeeds: Option<PathBuf>,
 
     /// A seed to use for the provenance mark chain, encoded as base64.
     /// If not supplied, a random seed is generated.
     #[arg(short, long, value_parser = seed::parse_seed)]
     seed: Option<ProvenanceSeed>,
 
-    /// The resolution of the provenance mark chain.
-    #[arg(short, long, default_value = "quartile")]
-    resolution: Resolution,
+    /// The resolution of the provenance mark chain. If not supplied, the
+    /// default from the user's `config.toml` is used, or else `quartile`.
+    #[arg(short, long)]
+    resolution: Option<Resolution>,
 
     /// A comment to be included for the genesis mark. (Comments are not part
     /// of the mark itself.)
@@ -38,20 +70,32 @@ pub struct CommandArgs {
     #[clap(value_parser = parse_date)]
     date: Option<Date>,
 
+    #[command(flatten)]
+    dates: DateCheckArgs,
+
     /// Suppress informational status output on stderr/stdout.
     #[arg(short, long)]
     quiet: bool,
 
-    /// Output format for the creation summary.
-    #[arg(long, value_enum, default_value_t = OutputFormat::Markdown)]
-    format: OutputFormat,
+    /// Output format for the creation summary. If not supplied, the default
+    /// from the user's `config.toml` is used, or else `markdown`.
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
 
     #[command(flatten)]
     info: InfoArgs,
+
+    #[command(flatten)]
+    metadata: MetadataArgs,
+
+    /// Show the genesis mark that would be generated, without creating the
+    /// chain's directory or closing the chain it succeeds.
+    #[arg(long)]
+    dry_run: bool,
 }
 
-#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
-enum Resolution {
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Resolution {
     /// Good for physical works of art and applications requiring minimal mark
     /// size.
     Low,
@@ -64,7 +108,7 @@ enum Resolution {
 }
 
 impl Resolution {
-    fn as_provenance_mark_resolution(&self) -> ProvenanceMarkResolution {
+    pub fn as_provenance_mark_resolution(&self) -> ProvenanceMarkResolution {
         match self {
             Resolution::Low => ProvenanceMarkResolution::Low,
             Resolution::Medium => ProvenanceMarkResolution::Medium,
@@ -72,32 +116,125 @@ impl Resolution {
             Resolution::High => ProvenanceMarkResolution::High,
         }
     }
+
+    pub fn from_provenance_mark_resolution(
+        res: ProvenanceMarkResolution,
+    ) -> Self {
+        match res {
+            ProvenanceMarkResolution::Low => Resolution::Low,
+            ProvenanceMarkResolution::Medium => Resolution::Medium,
+            ProvenanceMarkResolution::Quartile => Resolution::Quartile,
+            ProvenanceMarkResolution::High => Resolution::High,
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_possible_value() {
+            Some(value) => f.write_str(value.get_name()),
+            None => Ok(()),
+        }
+    }
 }
 
 impl crate::exec::Exec for CommandArgs {
     fn exec(&self) -> Result<String> {
-        // Create the directory, ensuring it doesn't already exist.
-        let path = self.create_dir()?;
+        // Never overwrite an existing generator kept elsewhere.
+        let generator_path =
+            self.generator.as_ref().map(read_new_path).transpose()?;
+        if let Some(generator_path) = &generator_path
+            && generator_path.exists()
+        {
+            bail!(
+                "Generator path already exists: {}",
+                generator_path.display()
+            );
+        }
+
+        // Check that the chain being succeeded can still be closed before
+        // creating anything.
+        let date = self.date.unwrap_or_else(Date::now);
+        let mut old_chain =
+            self.succeeds.as_ref().map(OldChain::open).transpose()?;
+        if let Some(old_chain) = &old_chain
+            && date < old_chain.head.date()
+        {
+            bail!(
+                "The genesis date {} is before the head of the chain it \
+                 succeeds, dated {}",
+                date,
+                old_chain.head.date()
+            );
+        }
 
-        // Create the `marks` subdirectory inside `path`.
-        let marks_path = path.join("marks");
-        fs::create_dir(&marks_path)?;
+        // Check the genesis date, rounded to what the new chain's resolution
+        // can encode, and that the old chain's closing mark can carry it too.
+        let user = UserConfig::load()?;
+        let resolution = match self.resolution {
+            Some(resolution) => resolution,
+            None => user.resolution()?.unwrap_or(Resolution::Quartile),
+        };
+        let date = self.dates.check(
+            resolution.as_provenance_mark_resolution(),
+            date,
+            None,
+        )?;
+        if let Some(old_chain) = &old_chain {
+            self.dates.check(*old_chain.generator.res(), date, None)?;
+        }
+
+        // Create the directory, ensuring it doesn't already exist, along with
+        // its `marks` subdirectory. A dry run only checks that it could.
+        let path = self.chain.path(&user)?;
+        let mut chain_dir = if self.dry_run {
+            ChainDir::plan(&path)?
+        } else {
+            ChainDir::create(&path)?
+        };
+        if !self.dry_run {
+            if let Some(generator_path) = generator_path {
+                chain_dir.set_generator_location(generator_path)?;
+            }
+            // Record the old chain's path relative to the new one, so that
+            // the link survives the two being moved or cloned together.
+            if let Some(old_chain) = &old_chain {
+                let old_path = old_chain.dir.path().canonicalize()?;
+                let new_path = chain_dir.path().canonicalize()?;
+                let predecessor = pathdiff::diff_paths(&old_path, &new_path)
+                    .unwrap_or(old_path);
+                chain_dir.set_predecessor(predecessor)?;
+            }
+        }
+
+        let format = match self.format {
+            Some(format) => format,
+            None => user.format()?.unwrap_or(OutputFormat::Markdown),
+        };
 
         let mut generator: ProvenanceMarkGenerator =
             if let Some(seed) = self.seed.clone() {
                 ProvenanceMarkGenerator::new_with_seed(
-                    self.resolution.as_provenance_mark_resolution(),
+                    resolution.as_provenance_mark_resolution(),
                     seed,
                 )
             } else {
                 ProvenanceMarkGenerator::new_random(
-                    self.resolution.as_provenance_mark_resolution(),
+                    resolution.as_provenance_mark_resolution(),
                 )
             };
 
-        // Generate the genesis mark.
-        let date = self.date.unwrap_or_else(Date::now);
-        let info = self.info.to_cbor()?;
+        // Generate the genesis mark. A successor's genesis mark names its
+        // predecessor's head beside any metadata.
+        let info = match (&old_chain, self.metadata.to_metadata()) {
+            (Some(old_chain), metadata) => Some(
+                Predecessor::of(&old_chain.head)
+                    .add_to(metadata.unwrap_or_default().to_envelope())
+                    .tagged_cbor(),
+            ),
+            (None, Some(metadata)) => Some(metadata.to_cbor()),
+            (None, None) => self.info.to_cbor()?,
+        };
         let mark = match info {
             Some(info_cbor) => generator.next(date, Some(info_cbor)),
             None => generator.next(date, None::<CBOR>),
@@ -105,47 +242,104 @@ impl crate::exec::Exec for CommandArgs {
         let mark_info =
             ProvenanceMarkInfo::new(mark.clone(), self.comment.clone());
 
+        if self.dry_run {
+            let mut status_lines = vec![
+                format!(
+                    "Provenance mark chain would be created at: {}",
+                    chain_dir.path().display()
+                ),
+                format!(
+                    "Mark {} would be written to: {} (dated {})",
+                    mark.seq(),
+                    chain_dir.mark_path(mark.seq()).display(),
+                    mark.date()
+                ),
+            ];
+            if let Some(old_chain) = &old_chain {
+                status_lines.push(format!(
+                    "Chain at {} would be closed with mark {}",
+                    old_chain.dir.path().display(),
+                    old_chain.generator.next_seq()
+                ));
+            }
+            return self.output(format, &status_lines, &mark_info);
+        }
+
         // Serialize the mark to JSON and write it as `mark-seq.json` to
         // `path/marks`.
-        let mark_json = serde_json::to_string_pretty(&mark_info)?;
-        let mark_path = marks_path.join(format!("mark-{}.json", mark.seq()));
-        fs::write(&mark_path, mark_json)?;
+        let mark_path = chain_dir.write_mark(&mark_info)?;
 
         // Serialize `generator` to JSON and write it as `generator.json` to
         // `path`.
-        let generator_json = serde_json::to_string_pretty(&generator)?;
-        let generator_path = path.join("generator.json");
-        fs::write(generator_path, generator_json)?;
+        chain_dir.write_generator(&generator)?;
 
         // Return a markdown summary of the provenance mark chain and the
         // genesis mark.
-        let status_lines = [
-            format!("Provenance mark chain created at: {}", path.display()),
-            format!("Mark {} written to: {}", mark.seq(), mark_path.display()),
+        let mut status_lines = vec![
+            format!(
+                "Provenance mark chain created at: {}",
+                chain_dir.path().display()
+            ),
+            format!(
+                "Mark {} written to: {} (dated {})",
+                mark.seq(),
+                mark_path.display(),
+                mark.date()
+            ),
         ];
 
-        match self.format {
+        // Close the old chain with a mark that commits to the new genesis
+        // mark.
+        if let Some(old_chain) = &mut old_chain {
+            let (seq, closing_path) = old_chain.close(&mark, date)?;
+            status_lines.push(format!(
+                "Chain at {} closed with mark {}, written to: {}",
+                old_chain.dir.path().display(),
+                seq,
+                closing_path.display()
+            ));
+        }
+
+        self.output(format, &status_lines, &mark_info)
+    }
+}
+
+impl CommandArgs {
+    /// The genesis mark in the chosen format, with the status lines before
+    /// it. The note for a dry run is shown even when `--quiet` is given.
+    fn output(
+        &self,
+        format: OutputFormat,
+        status_lines: &[String],
+        mark_info: &ProvenanceMarkInfo,
+    ) -> Result<String> {
+        let mut shown: Vec<&str> = Vec::new();
+        if !self.quiet {
+            shown.extend(status_lines.iter().map(String::as_str));
+        }
+        if self.dry_run {
+            shown.push(if self.seed.is_some() {
+                DRY_RUN_NOTE
+            } else {
+                RANDOM_SEED_DRY_RUN_NOTE
+            });
+        }
+        match format {
             OutputFormat::Markdown => {
-                let mut paragraphs: Vec<String> = Vec::new();
-                if !self.quiet {
-                    paragraphs.extend(status_lines.iter().cloned());
-                }
-                paragraphs.push(mark_info.markdown_summary());
+                let summary = mark_info.markdown_summary();
+                let mut paragraphs = shown;
+                paragraphs.push(&summary);
                 Ok(paragraphs.join("\n\n"))
             }
             OutputFormat::Ur => {
-                if !self.quiet {
-                    for line in &status_lines {
-                        eprintln!("{}", line);
-                    }
+                for line in shown {
+                    eprintln!("{}", line);
                 }
                 Ok(mark_info.ur().to_string())
             }
             OutputFormat::Json => {
-                if !self.quiet {
-                    for line in &status_lines {
-                        eprintln!("{}", line);
-                    }
+                for line in shown {
+                    eprintln!("{}", line);
                 }
                 serde_json::to_string_pretty(&mark_info).map_err(Into::into)
             }
@@ -153,27 +347,57 @@ impl crate::exec::Exec for CommandArgs {
     }
 }
 
-impl CommandArgs {
-    fn create_dir(&self) -> Result<PathBuf> {
-        let path = read_new_path(&self.path)?;
+/// A chain being succeeded by a new chain, which must have a generator and
+/// must not already be closed.
+struct OldChain {
+    dir: ChainDir,
+    generator: ProvenanceMarkGenerator,
+    head: ProvenanceMark,
+}
 
-        // Ensure the directory doesn't already exist.
-        if path.exists() {
-            bail!("Path already exists: {}", path.display());
+impl OldChain {
+    fn open(path: &PathBuf) -> Result<Self> {
+        let dir = ChainDir::open(path)?;
+        if dir.is_mirror() {
+            bail!(
+                "Cannot succeed a mirror, which has no generator: {}",
+                dir.path().display()
+            );
         }
-
-        // Ensure the parent directory exists.
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
-                bail!("Parent directory does not exist: {}", parent.display());
-            }
-        } else {
-            bail!("Path has no parent directory: {}", path.display());
+        if dir.is_closed()? {
+            bail!(
+                "The chain at {} is closed; it cannot be succeeded.",
+                dir.path().display()
+            );
         }
+        let generator = dir.read_generator()?;
+        let head = dir.read_mark(generator.next_seq() - 1)?.mark().clone();
+        Ok(Self {
+            dir,
+            generator,
+            head,
+        })
+    }
 
-        // Create the new directory.
-        fs::create_dir(&path)?;
-
-        Ok(path)
+    /// Issue the chain's closing mark, naming `genesis` as its successor,
+    /// and close the chain. Returns the closing mark's sequence number and
+    /// the path it was written to.
+    fn close(
+        &mut self,
+        genesis: &ProvenanceMark,
+        date: Date,
+    ) -> Result<(u32, PathBuf)> {
+        let closure = ChainClosure {
+            reason: format!(
+                "Succeeded by chain {}",
+                hex::encode(genesis.chain_id())
+            ),
+            successor: Some(Successor::of(genesis)),
+        };
+        let mark = self.generator.next(date, Some(closure.to_cbor()));
+        let mark_info = ProvenanceMarkInfo::new(mark.clone(), "Chain closed.");
+        let mark_path = self.dir.write_mark(&mark_info)?;
+        self.dir.write_closed_generator(&self.generator)?;
+        Ok((mark.seq(), mark_path))
     }
 }
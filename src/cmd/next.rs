@@ -1,101 +1,362 @@
-use std::{fs, path::PathBuf};
+mod batch;
 
-use anyhow::Result;
+use std::{fs, path::PathBuf, time::Duration};
+
+use anyhow::{Result, bail};
 use clap::Args;
 use dcbor::prelude::*;
-use provenance_mark::{
-    ProvenanceMarkGenerator, ProvenanceMarkInfo, util::parse_date,
-};
+use provenance_mark::{ProvenanceMarkInfo, util::parse_date};
 
-use super::{info::InfoArgs, print::OutputFormat};
-use crate::utils::read_existing_directory_path;
+use super::{
+    chain::{ChainArgs, GeneratorArgs},
+    date_check::DateCheckArgs,
+    heartbeat::heartbeat_cbor,
+    info::InfoArgs,
+    print::OutputFormat,
+};
+use crate::{
+    chain_dir::ChainDir,
+    config::{UserConfig, default_comment, default_format},
+    idempotency::IdempotencyKeys,
+    publications::Publications,
+    utils::{format_duration, parse_duration},
+};
 
 /// Generate the next provenance mark in a chain.
 #[derive(Debug, Args)]
 #[group(skip)]
 pub struct CommandArgs {
-    /// Path to the chain's directory. Must already exist.
-    path: PathBuf,
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    #[command(flatten)]
+    generator: GeneratorArgs,
 
     /// A comment to be included for the mark. (Comments are not part of the
-    /// mark itself.)
-    #[arg(short, long, default_value = "Blank.")]
-    comment: String,
+    /// mark itself.) If not supplied, the default from the chain's
+    /// `chain.toml` or the user's `config.toml` is used, or else `Blank.`.
+    #[arg(short, long)]
+    comment: Option<String>,
 
     /// The date of the next mark. If not supplied, the current date is used.
     #[arg(short, long, value_parser = parse_date)]
     date: Option<Date>,
 
+    #[command(flatten)]
+    dates: DateCheckArgs,
+
     /// Suppress informational status output on stderr/stdout.
     #[arg(short, long)]
     quiet: bool,
 
-    /// Output format for the mark.
-    #[arg(long, value_enum, default_value_t = OutputFormat::Markdown)]
-    format: OutputFormat,
+    /// Output format for the mark. If not supplied, the default from the
+    /// chain's `chain.toml` or the user's `config.toml` is used, or else
+    /// `markdown`.
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
 
     #[command(flatten)]
     info: InfoArgs,
+
+    /// Show the mark that would be generated, without writing it or
+    /// advancing the chain's generator.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Path to a manifest of marks to generate in order, as a `.csv` file
+    /// with a header row or a `.jsonl` file with one object per line. Each
+    /// row may give a `comment`, `date`, `info`, and `info-tag`. If any row
+    /// is invalid, no marks are generated.
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["comment", "date", "info", "info_tag"]
+    )]
+    batch: Option<PathBuf>,
+
+    /// A key, such as a CI build ID, that identifies this request for a mark.
+    /// If a mark has already been generated with the same key, it is output
+    /// again instead of generating another. The key must come with the same
+    /// comment and info as before.
+    #[arg(long, value_name = "KEY", conflicts_with = "batch")]
+    idempotency_key: Option<String>,
+
+    /// Generate a heartbeat mark, showing that the chain is still
+    /// controlled, only if the chain's last mark is older than this, such as
+    /// `7d`. Otherwise nothing is generated. A heartbeat mark's `info` is a
+    /// standard `ProvenanceHeartbeat` envelope, and its comment defaults to
+    /// `Heartbeat.`.
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        conflicts_with_all = ["batch", "info", "info_tag"]
+    )]
+    if_older_than: Option<Duration>,
 }
 
+/// Shown with the output of a dry run, whose mark is only a preview.
+pub const DRY_RUN_NOTE: &str = "Dry run: nothing was written. The real mark \
+    will match this preview only if the generator is advanced in the same \
+    way; if another mark is generated first, or its random number generator \
+    is stepped differently, the real mark's key and hash will differ.";
+
 impl crate::exec::Exec for CommandArgs {
     fn exec(&self) -> Result<String> {
-        // Get the chain's directory path.
-        let path = read_existing_directory_path(&self.path)?;
-
-        // Read the generator from `path/generator.json`.
-        let generator_path = path.join("generator.json");
-        let generator_json = fs::read_to_string(&generator_path)?;
-        let mut generator: ProvenanceMarkGenerator =
-            serde_json::from_str(&generator_json)?;
-
-        // Generate the next mark.
-        let date = self.date.unwrap_or_else(Date::now);
-        let info = self.info.to_cbor()?;
-        let mark = match info {
-            Some(info_cbor) => generator.next(date, Some(info_cbor)),
-            None => generator.next(date, None::<CBOR>),
+        // Get the chain's directory.
+        let user = UserConfig::load()?;
+        let chain_dir = ChainDir::open(&self.chain.path(&user)?)?
+            .with_generator(self.generator.path())?;
+        let format = match self.format {
+            Some(format) => format,
+            None => default_format(chain_dir.config(), &user)?
+                .unwrap_or(OutputFormat::Markdown),
         };
-        let mark_info =
-            ProvenanceMarkInfo::new(mark.clone(), self.comment.clone());
-
-        // Serialize the mark to JSON and write it as `mark-seq.json` to
-        // `path/marks`.
-        let marks_path = path.join("marks");
-        let mark_json = serde_json::to_string_pretty(&mark_info)?;
-        let mark_path = marks_path.join(format!("mark-{}.json", mark.seq()));
-        fs::write(&mark_path, mark_json)?;
-
-        // Serialize `generator` to JSON and write it back to
-        // `path/generator.json`.
-        let generator_json = serde_json::to_string_pretty(&generator)?;
-        fs::write(generator_path, generator_json)?;
-
-        // Return output based on format.
-        let status_line =
-            format!("Mark {} written to: {}", mark.seq(), mark_path.display());
-
-        match self.format {
+
+        // Read every row of a batch before generating anything, so that an
+        // invalid row leaves the chain unchanged.
+        let default_comment = default_comment(chain_dir.config(), &user)
+            .unwrap_or_else(|| "Blank.".to_string());
+        let entries = match &self.batch {
+            Some(path) => batch::read(path)?,
+            None if self.if_older_than.is_some() => vec![batch::Entry {
+                comment: self
+                    .comment
+                    .clone()
+                    .or_else(|| Some("Heartbeat.".to_string())),
+                date: Some(self.date.unwrap_or_else(Date::now)),
+                info: Some(heartbeat_cbor()),
+            }],
+            None => vec![batch::Entry {
+                comment: self.comment.clone(),
+                date: self.date,
+                info: self.info.to_cbor()?,
+            }],
+        };
+
+        // A retried request gets the mark generated the first time.
+        let mut keys = IdempotencyKeys::load(chain_dir.path())?;
+        if let Some(key) = &self.idempotency_key
+            && let Some(seq) = keys.get(key)
+        {
+            let mark_info = chain_dir.read_mark(seq)?;
+            let entry = &entries[0];
+            let comment = entry.comment.as_ref().unwrap_or(&default_comment);
+            if mark_info.comment() != comment
+                || mark_info.mark().info() != entry.info
+            {
+                bail!(
+                    "Idempotency key `{}` was used for mark {} with a \
+                     different comment or info.",
+                    key,
+                    seq
+                );
+            }
+            let status_line = format!(
+                "Mark {} was already generated for idempotency key `{}`: {}",
+                seq,
+                key,
+                chain_dir.mark_path(seq).display()
+            );
+            // Even in a dry run this is the existing mark, not a preview.
+            return self.output(format, vec![status_line], &[mark_info], false);
+        }
+
+        if chain_dir.is_closed()? {
+            bail!("The chain is closed; no more marks can be added to it.");
+        }
+
+        // A heartbeat is only needed once the chain has been quiet for long
+        // enough.
+        if let Some(max_age) = self.if_older_than
+            && let Some(date) = entries[0].date
+        {
+            let head = chain_dir.read_mark(chain_dir.last_seq()?)?;
+            let age = date.timestamp() - head.mark().date().timestamp();
+            if age < max_age.as_secs_f64() {
+                let status_line = format!(
+                    "Mark {} is {} old, newer than {}; no mark was generated.",
+                    head.mark().seq(),
+                    format_duration(age),
+                    format_duration(max_age.as_secs_f64())
+                );
+                if self.quiet {
+                    return Ok(String::new());
+                }
+                return match format {
+                    OutputFormat::Markdown => Ok(status_line),
+                    OutputFormat::Ur | OutputFormat::Json => {
+                        eprintln!("{}", status_line);
+                        Ok(String::new())
+                    }
+                };
+            }
+        }
+
+        // Read the generator from `path/generator.json`. A dry run advances
+        // this copy without saving it.
+        let mut generator = chain_dir.read_generator()?;
+
+        // Generate the marks, keeping the generator's state before each so
+        // that it can be undone. Each date is checked against the mark before
+        // it, and rounded to what the chain's resolution can encode.
+        let mut previous =
+            chain_dir.read_mark(generator.next_seq() - 1)?.mark().date();
+        let mut snapshots = Vec::new();
+        let mut mark_infos = Vec::new();
+        for entry in entries {
+            snapshots.push(generator.clone());
+            let date = self.dates.check(
+                *generator.res(),
+                entry.date.unwrap_or_else(Date::now),
+                Some(previous),
+            )?;
+            previous = date;
+            let mark = match entry.info {
+                Some(info_cbor) => generator.next(date, Some(info_cbor)),
+                None => generator.next(date, None::<CBOR>),
+            };
+            let comment =
+                entry.comment.unwrap_or_else(|| default_comment.clone());
+            mark_infos.push(ProvenanceMarkInfo::new(mark, comment));
+        }
+
+        let status_lines = if self.dry_run {
+            mark_infos
+                .iter()
+                .map(|mark_info| {
+                    let seq = mark_info.mark().seq();
+                    format!(
+                        "Mark {} would be written to: {} (dated {})",
+                        seq,
+                        chain_dir.mark_path(seq).display(),
+                        mark_info.mark().date()
+                    )
+                })
+                .collect()
+        } else {
+            // Serialize each mark to JSON and write it as `mark-seq.json` to
+            // `path/marks`.
+            let mark_paths = write_marks(&chain_dir, &mark_infos)?;
+
+            // Save the generator's state before each mark, then serialize
+            // `generator` to JSON and write it back to `path/generator.json`,
+            // once every mark is written.
+            let saved = snapshots
+                .iter()
+                .try_for_each(|snapshot| chain_dir.save_snapshot(snapshot))
+                .and_then(|()| chain_dir.write_generator(&generator));
+            if let Err(e) = saved {
+                remove_marks(&mark_paths);
+                return Err(e);
+            }
+            chain_dir.prune_snapshots(
+                generator.next_seq() - 1,
+                Publications::load(chain_dir.path())?.last_published(),
+            )?;
+
+            // Remember which mark this request's key produced.
+            if let Some(key) = &self.idempotency_key {
+                keys.insert(key.clone(), mark_infos[0].mark().seq());
+                keys.save(chain_dir.path())?;
+            }
+
+            mark_infos
+                .iter()
+                .zip(&mark_paths)
+                .map(|(mark_info, mark_path)| {
+                    format!(
+                        "Mark {} written to: {} (dated {})",
+                        mark_info.mark().seq(),
+                        mark_path.display(),
+                        mark_info.mark().date()
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        self.output(format, status_lines, &mark_infos, self.dry_run)
+    }
+}
+
+impl CommandArgs {
+    /// The marks in the chosen format, with the status lines before them.
+    fn output(
+        &self,
+        format: OutputFormat,
+        status_lines: Vec<String>,
+        mark_infos: &[ProvenanceMarkInfo],
+        preview: bool,
+    ) -> Result<String> {
+        match format {
             OutputFormat::Markdown => {
                 let mut paragraphs: Vec<String> = Vec::new();
                 if !self.quiet {
-                    paragraphs.push(status_line);
+                    paragraphs.extend(status_lines);
                 }
-                paragraphs.push(mark_info.markdown_summary());
+                if preview {
+                    paragraphs.push(DRY_RUN_NOTE.to_string());
+                }
+                paragraphs.extend(
+                    mark_infos.iter().map(|info| info.markdown_summary()),
+                );
                 Ok(paragraphs.join("\n\n"))
             }
             OutputFormat::Ur => {
-                if !self.quiet {
-                    eprintln!("{}", status_line);
-                }
-                Ok(mark_info.ur().to_string())
+                self.print_status(&status_lines, preview);
+                let urs: Vec<String> = mark_infos
+                    .iter()
+                    .map(|info| info.ur().to_string())
+                    .collect();
+                Ok(urs.join("\n"))
             }
             OutputFormat::Json => {
-                if !self.quiet {
-                    eprintln!("{}", status_line);
+                self.print_status(&status_lines, preview);
+                if self.batch.is_some() {
+                    serde_json::to_string_pretty(mark_infos).map_err(Into::into)
+                } else {
+                    serde_json::to_string_pretty(&mark_infos[0])
+                        .map_err(Into::into)
                 }
-                serde_json::to_string_pretty(&mark_info).map_err(Into::into)
             }
         }
     }
+
+    /// Write the status lines, and the note for a preview, to stderr.
+    fn print_status(&self, status_lines: &[String], preview: bool) {
+        if !self.quiet {
+            for line in status_lines {
+                eprintln!("{}", line);
+            }
+        }
+        if preview {
+            eprintln!("{}", DRY_RUN_NOTE);
+        }
+    }
+}
+
+/// Write each mark, returning the paths written. If any mark cannot be
+/// written, those already written are removed so that the chain is left as
+/// it was.
+fn write_marks(
+    chain_dir: &ChainDir,
+    mark_infos: &[ProvenanceMarkInfo],
+) -> Result<Vec<PathBuf>> {
+    let mut mark_paths = Vec::new();
+    for mark_info in mark_infos {
+        match chain_dir.write_mark(mark_info) {
+            Ok(mark_path) => mark_paths.push(mark_path),
+            Err(e) => {
+                remove_marks(&mark_paths);
+                return Err(e);
+            }
+        }
+    }
+    Ok(mark_paths)
+}
+
+fn remove_marks(mark_paths: &[PathBuf]) {
+    for mark_path in mark_paths {
+        let _ = fs::remove_file(mark_path);
+    }
 }
@@ -0,0 +1,113 @@
+use std::{fs, path::Path};
+
+use anyhow::{Result, anyhow, bail};
+use dcbor::prelude::*;
+use provenance_mark::util::parse_date;
+use serde::Deserialize;
+
+use crate::cmd::info::InfoArgs;
+
+/// One mark to generate, as a row of a batch manifest. Each field is
+/// optional, with the same meaning as the `next` option of the same name.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Row {
+    comment: Option<String>,
+    date: Option<String>,
+    info: Option<String>,
+    info_tag: Option<u64>,
+}
+
+/// A row of a batch manifest, checked and ready to generate.
+#[derive(Debug)]
+pub struct Entry {
+    pub comment: Option<String>,
+    pub date: Option<Date>,
+    pub info: Option<CBOR>,
+}
+
+/// Read every row of a batch manifest: a `.csv` file with a header row, or a
+/// `.jsonl` file with one object per line. Rows must be dated in order. If
+/// any row is invalid, nothing is returned.
+pub fn read(path: &Path) -> Result<Vec<Entry>> {
+    let rows = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => read_csv(path)?,
+        Some("jsonl") => read_jsonl(path)?,
+        _ => bail!(
+            "Batch manifest must be a .csv or .jsonl file: {}",
+            path.display()
+        ),
+    };
+    if rows.is_empty() {
+        bail!("Batch manifest has no rows: {}", path.display());
+    }
+
+    let mut entries: Vec<Entry> = Vec::new();
+    for (index, row) in rows.into_iter().enumerate() {
+        let entry = row_entry(row, entries.last()).map_err(|e| {
+            anyhow!("Row {} of {}: {}", index + 1, path.display(), e)
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn read_csv(path: &Path) -> Result<Vec<Row>> {
+    let mut reader = csv::Reader::from_path(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    reader
+        .deserialize()
+        .enumerate()
+        .map(|(index, row)| {
+            row.map_err(|e| {
+                anyhow!("Row {} of {}: {}", index + 1, path.display(), e)
+            })
+        })
+        .collect()
+}
+
+fn read_jsonl(path: &Path) -> Result<Vec<Row>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                anyhow!("Row {} of {}: {}", index + 1, path.display(), e)
+            })
+        })
+        .collect()
+}
+
+fn row_entry(row: Row, previous: Option<&Entry>) -> Result<Entry> {
+    let date = row
+        .date
+        .filter(|date| !date.trim().is_empty())
+        .map(|date| {
+            parse_date(date.trim())
+                .map_err(|e| anyhow!("invalid date {}: {}", date, e))
+        })
+        .transpose()?;
+    if let (
+        Some(date),
+        Some(Entry {
+            date: Some(previous),
+            ..
+        }),
+    ) = (date, previous)
+        && date < *previous
+    {
+        bail!("dated {}, before the previous row's {}", date, previous);
+    }
+    let info = InfoArgs {
+        info: row.info.filter(|info| !info.trim().is_empty()),
+        info_tag: row.info_tag,
+    }
+    .to_cbor()?;
+    Ok(Entry {
+        comment: row.comment.filter(|comment| !comment.is_empty()),
+        date,
+        info,
+    })
+}
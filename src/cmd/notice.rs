@@ -0,0 +1,132 @@
+mod add;
+mod create;
+
+use anyhow::{Result, anyhow, bail};
+use bc_envelope::prelude::*;
+use clap::{Args, Subcommand};
+use provenance_mark::ProvenanceMark;
+
+use crate::exec::Exec;
+
+/// The subject of a compromise notice's envelope.
+pub const NOTICE_SUBJECT: &str = "ProvenanceCompromiseNotice";
+
+/// Create compromise notices and add them to the trust store.
+///
+/// A compromise notice says that a chain's generator has leaked, and names
+/// the last mark, by sequence number or date, that can still be trusted.
+/// `validate` reports every later mark of the chain as untrusted.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Create(create::CommandArgs),
+    Add(add::CommandArgs),
+}
+
+impl Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        match &self.command {
+            Commands::Create(args) => args.exec(),
+            Commands::Add(args) => args.exec(),
+        }
+    }
+}
+
+/// The last point of a chain that a compromise notice still trusts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cutoff {
+    Seq(u32),
+    Date(Date),
+}
+
+/// A statement that a chain's generator has been compromised, so that its
+/// marks after a cutoff cannot be trusted.
+///
+/// The notice is an envelope whose subject is `ProvenanceCompromiseNotice`,
+/// with the chain ID as a `chain` assertion, the cutoff as a
+/// `lastTrustedSeq` or `lastTrustedDate` assertion, and optional `reason` and
+/// `issued` assertions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompromiseNotice {
+    pub chain_id: Vec<u8>,
+    pub cutoff: Cutoff,
+    pub reason: Option<String>,
+    pub issued: Option<Date>,
+}
+
+impl CompromiseNotice {
+    pub fn to_envelope(&self) -> Envelope {
+        let envelope = Envelope::new(NOTICE_SUBJECT)
+            .add_assertion("chain", CBOR::to_byte_string(&self.chain_id));
+        let envelope = match &self.cutoff {
+            Cutoff::Seq(seq) => envelope.add_assertion("lastTrustedSeq", *seq),
+            Cutoff::Date(date) => {
+                envelope.add_assertion("lastTrustedDate", *date)
+            }
+        };
+        envelope
+            .add_optional_assertion("reason", self.reason.clone())
+            .add_optional_assertion("issued", self.issued)
+    }
+
+    pub fn from_envelope(envelope: &Envelope) -> Result<Self> {
+        if envelope.extract_subject::<String>().ok().as_deref()
+            != Some(NOTICE_SUBJECT)
+        {
+            bail!("Not a compromise notice");
+        }
+        let chain_id = envelope
+            .object_for_predicate("chain")?
+            .try_leaf()?
+            .try_into_byte_string()
+            .map_err(|_| anyhow!("The notice's `chain` is not a chain ID"))?;
+        let seq = envelope
+            .extract_optional_object_for_predicate::<u32>("lastTrustedSeq")?;
+        let date = envelope
+            .extract_optional_object_for_predicate::<Date>("lastTrustedDate")?;
+        let cutoff = match (seq, date) {
+            (Some(seq), None) => Cutoff::Seq(seq),
+            (None, Some(date)) => Cutoff::Date(date),
+            _ => bail!(
+                "A compromise notice needs exactly one of `lastTrustedSeq` \
+                 and `lastTrustedDate`"
+            ),
+        };
+        Ok(Self {
+            chain_id,
+            cutoff,
+            reason: envelope.extract_optional_object_for_predicate("reason")?,
+            issued: envelope.extract_optional_object_for_predicate("issued")?,
+        })
+    }
+
+    pub fn from_ur_string(ur_string: &str) -> Result<Self> {
+        let envelope = Envelope::from_ur_string(ur_string)
+            .map_err(|e| anyhow!("Invalid compromise notice: {}", e))?;
+        Self::from_envelope(&envelope)
+    }
+
+    /// Whether the notice applies to the mark, which comes after the cutoff
+    /// of its chain.
+    pub fn distrusts(&self, mark: &ProvenanceMark) -> bool {
+        mark.chain_id() == self.chain_id.as_slice()
+            && match &self.cutoff {
+                Cutoff::Seq(seq) => mark.seq() > *seq,
+                Cutoff::Date(date) => mark.date() > *date,
+            }
+    }
+
+    /// Describe the notice's cutoff, as in "marks after mark 12".
+    pub fn describe_cutoff(&self) -> String {
+        match &self.cutoff {
+            Cutoff::Seq(seq) => format!("marks after mark {}", seq),
+            Cutoff::Date(date) => format!("marks dated after {}", date),
+        }
+    }
+}
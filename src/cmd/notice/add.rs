@@ -0,0 +1,41 @@
+use anyhow::Result;
+use clap::Args;
+
+use super::CompromiseNotice;
+use crate::{trust_store::TrustStore, utils::read_argument};
+
+/// Add a compromise notice to the trust store, so that `validate` reports the
+/// marks it distrusts.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    /// The notice, as written by `notice create`. If not supplied, it is read
+    /// from stdin.
+    #[arg(value_name = "UR")]
+    notice: Option<String>,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let notice = CompromiseNotice::from_ur_string(
+            read_argument(self.notice.as_deref())?.trim(),
+        )?;
+        let (path, added) = TrustStore::add(&notice)?;
+        let chain_id = hex::encode(&notice.chain_id);
+        Ok(if added {
+            format!(
+                "Added compromise notice for chain {}: {} are untrusted.\n\
+                 Stored at: {}",
+                chain_id,
+                notice.describe_cutoff(),
+                path.display()
+            )
+        } else {
+            format!(
+                "The trust store already holds this notice for chain {}: {}",
+                chain_id,
+                path.display()
+            )
+        })
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{Result, anyhow, bail};
+use bc_envelope::prelude::*;
+use clap::Args;
+use provenance_mark::util::parse_date;
+
+use super::{CompromiseNotice, Cutoff};
+use crate::{chain_dir::ChainDir, cmd::chain::ChainArgs, config::UserConfig};
+
+/// Write a compromise notice for a chain as a `ur:envelope`.
+///
+/// The notice is not signed; publish it through a channel that the chain's
+/// verifiers already trust, such as the place the chain's marks are
+/// published.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    /// The ID of the compromised chain in hex, for a chain whose directory is
+    /// not at hand.
+    #[arg(long, value_name = "HEX", conflicts_with_all = ["path", "chain"])]
+    chain_id: Option<String>,
+
+    /// The sequence number of the last mark that can be trusted.
+    #[arg(
+        long,
+        value_name = "SEQ",
+        required_unless_present = "last_trusted_date",
+        conflicts_with = "last_trusted_date"
+    )]
+    last_trusted_seq: Option<u32>,
+
+    /// The date after which marks cannot be trusted.
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    last_trusted_date: Option<Date>,
+
+    /// How the generator was compromised.
+    #[arg(long)]
+    reason: Option<String>,
+
+    /// The date of the notice. If not supplied, the current date is used.
+    #[arg(short, long, value_parser = parse_date)]
+    date: Option<Date>,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let chain_id = match &self.chain_id {
+            Some(chain_id) => hex::decode(chain_id.trim())
+                .map_err(|e| anyhow!("Invalid chain ID: {}", e))?,
+            None => {
+                let user = UserConfig::load()?;
+                let chain_dir = ChainDir::open(&self.chain.path(&user)?)?;
+                let last_seq = chain_dir.last_seq()?;
+                if let Some(seq) = self.last_trusted_seq
+                    && seq > last_seq
+                {
+                    bail!(
+                        "The chain's last mark is {}; there is no mark {}.",
+                        last_seq,
+                        seq
+                    );
+                }
+                chain_dir.read_mark(last_seq)?.mark().chain_id().to_vec()
+            }
+        };
+        let cutoff = match (self.last_trusted_seq, &self.last_trusted_date) {
+            (Some(seq), _) => Cutoff::Seq(seq),
+            (None, Some(date)) => Cutoff::Date(*date),
+            (None, None) => bail!(
+                "A cutoff is required; supply --last-trusted-seq or \
+                 --last-trusted-date."
+            ),
+        };
+        let notice = CompromiseNotice {
+            chain_id,
+            cutoff,
+            reason: self.reason.clone(),
+            issued: Some(self.date.unwrap_or_else(Date::now)),
+        };
+        Ok(notice.to_envelope().ur_string())
+    }
+}
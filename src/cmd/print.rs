@@ -1,17 +1,28 @@
-use std::{fs, path::PathBuf};
+use std::io::Write;
 
 use anyhow::{Result, bail};
 use clap::{Args, ValueEnum};
-use provenance_mark::{ProvenanceMarkGenerator, ProvenanceMarkInfo};
+use serde::ser::{SerializeSeq, Serializer};
 
-use crate::utils::read_existing_directory_path;
+use super::{
+    chain::{ChainArgs, GeneratorArgs},
+    metadata::ChainMetadata,
+};
+use crate::{
+    chain_dir::ChainDir,
+    config::{UserConfig, default_format},
+    publications::Publications,
+};
 
 /// Prints provenance marks in a chain.
 #[derive(Debug, Args)]
 #[group(skip)]
 pub struct CommandArgs {
-    /// Path to the chain's directory. Must already exist.
-    path: PathBuf,
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    #[command(flatten)]
+    generator: GeneratorArgs,
 
     /// The sequence number of the first mark to print. If not supplied, the
     /// first mark (genesis mark) is used.
@@ -23,9 +34,11 @@ pub struct CommandArgs {
     #[arg(short, long)]
     end: Option<u32>,
 
-    /// Output format for the rendered marks.
-    #[arg(long, value_enum, default_value_t = OutputFormat::Markdown)]
-    format: OutputFormat,
+    /// Output format for the rendered marks. If not supplied, the default
+    /// from the chain's `chain.toml` or the user's `config.toml` is used, or
+    /// else `markdown`.
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
@@ -37,17 +50,25 @@ pub enum OutputFormat {
 
 impl crate::exec::Exec for CommandArgs {
     fn exec(&self) -> Result<String> {
-        // Get the chain's directory path.
-        let path = read_existing_directory_path(&self.path)?;
+        let mut output = Vec::new();
+        self.exec_to(&mut output)?;
+        let mut output = String::from_utf8(output)?;
+        if output.ends_with('\n') {
+            output.pop();
+        }
+        Ok(output)
+    }
 
-        // Read the generator from `path/generator.json`.
-        let generator_path = path.join("generator.json");
-        let generator_json = fs::read_to_string(generator_path)?;
-        let generator: ProvenanceMarkGenerator =
-            serde_json::from_str(&generator_json)?;
+    /// Write each mark as soon as it is read, so that printing a long chain
+    /// does not hold every mark in memory.
+    fn exec_to(&self, out: &mut dyn Write) -> Result<()> {
+        // Get the chain's directory.
+        let user = UserConfig::load()?;
+        let chain_dir = ChainDir::open(&self.chain.path(&user)?)?
+            .with_generator(self.generator.path())?;
 
         // Validate the start and end sequence numbers.
-        let last_valid_seq = generator.next_seq() - 1;
+        let last_valid_seq = chain_dir.last_seq()?;
         let start_seq = self.start;
         let end_seq = self.end.unwrap_or(last_valid_seq);
         if start_seq > end_seq {
@@ -61,35 +82,66 @@ impl crate::exec::Exec for CommandArgs {
             );
         }
 
-        // Collect the requested marks.
-        let mut mark_infos: Vec<ProvenanceMarkInfo> = Vec::new();
-        for seq in start_seq..=end_seq {
-            let mark_path =
-                path.join("marks").join(format!("mark-{}.json", seq));
-            let mark_json = fs::read_to_string(&mark_path)?;
-            let mark_info: ProvenanceMarkInfo =
-                serde_json::from_str(&mark_json)?;
-            mark_infos.push(mark_info);
-        }
+        // Read the requested marks one at a time.
+        let mark_infos =
+            (start_seq..=end_seq).map(|seq| chain_dir.read_mark(seq));
 
-        match self.format {
-            OutputFormat::Markdown => {
-                let summaries: Vec<String> = mark_infos
-                    .iter()
-                    .map(ProvenanceMarkInfo::markdown_summary)
-                    .collect();
-                Ok(summaries.join("\n"))
-            }
-            OutputFormat::Ur => {
-                let urs: Vec<String> = mark_infos
-                    .iter()
-                    .map(|info| info.ur().to_string())
-                    .collect();
-                Ok(urs.join("\n"))
+        let format = match self.format {
+            Some(format) => format,
+            None => default_format(chain_dir.config(), &user)?
+                .unwrap_or(OutputFormat::Markdown),
+        };
+        match format {
+            OutputFormat::Markdown | OutputFormat::Ur => {
+                for (index, mark_info) in mark_infos.enumerate() {
+                    let mark_info = mark_info?;
+                    if index > 0 {
+                        writeln!(out)?;
+                    }
+                    if format == OutputFormat::Markdown {
+                        write!(out, "{}", mark_info.markdown_summary())?;
+                        if let Some(metadata) =
+                            ChainMetadata::from_mark(mark_info.mark())
+                        {
+                            let items: Vec<String> = metadata
+                                .lines()
+                                .iter()
+                                .map(|line| format!("- {}", line))
+                                .collect();
+                            write!(out, "\n\n{}", items.join("\n"))?;
+                        }
+                    } else {
+                        write!(out, "{}", mark_info.ur())?;
+                    }
+                }
+                writeln!(out)?;
             }
             OutputFormat::Json => {
-                serde_json::to_string_pretty(&mark_infos).map_err(Into::into)
+                let publications = Publications::load(chain_dir.path())?;
+
+                // Serialize the array element by element; the result is the
+                // same as serializing the whole array at once.
+                let mut serializer = serde_json::Serializer::pretty(&mut *out);
+                let mut seq = serializer.serialize_seq(None)?;
+                for mark_info in mark_infos {
+                    let mark_info = mark_info?;
+                    let mut element = serde_json::to_value(&mark_info)?;
+                    if let Some(metadata) =
+                        ChainMetadata::from_mark(mark_info.mark())
+                    {
+                        element["metadata"] = serde_json::to_value(metadata)?;
+                    }
+                    let published =
+                        publications.for_mark(mark_info.mark().seq());
+                    if !published.is_empty() {
+                        element["publications"] = published.into();
+                    }
+                    seq.serialize_element(&element)?;
+                }
+                seq.end()?;
+                writeln!(out)?;
             }
         }
+        Ok(())
     }
 }
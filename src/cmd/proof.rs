@@ -0,0 +1,120 @@
+use anyhow::{Result, anyhow, bail};
+use bc_envelope::prelude::*;
+use clap::Args;
+use provenance_mark::ProvenanceMark;
+
+use super::chain::ChainArgs;
+use crate::{chain_dir::ChainDir, config::UserConfig};
+
+/// Produce a proof that a range of a chain's marks descend from an earlier
+/// mark.
+///
+/// The proof is a `ur:envelope` bundle whose subject is the chain ID and which
+/// holds each mark in the range as a 'provenance' assertion. It can be checked
+/// offline with `verify-proof`.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    /// The sequence number of the first mark in the proof. If not supplied,
+    /// the proof starts at the genesis mark.
+    #[arg(short, long, default_value = "0")]
+    from: u32,
+
+    /// The sequence number of the last mark in the proof. If not supplied, the
+    /// last mark in the chain is used.
+    #[arg(short, long)]
+    to: Option<u32>,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let user = UserConfig::load()?;
+        let chain_dir = ChainDir::open(&self.chain.path(&user)?)?;
+
+        // Validate the range of sequence numbers.
+        let last_valid_seq = chain_dir.last_seq()?;
+        let from_seq = self.from;
+        let to_seq = self.to.unwrap_or(last_valid_seq);
+        if from_seq > to_seq {
+            bail!(
+                "The first sequence number must be less than or equal to the last sequence number."
+            );
+        }
+        if to_seq > last_valid_seq {
+            bail!(
+                "The last sequence number must be less than or equal to the last valid sequence number."
+            );
+        }
+
+        // Read the marks, checking that each one links to the next so that
+        // the proof is never emitted for a broken chain.
+        let mut marks: Vec<ProvenanceMark> = Vec::new();
+        for seq in from_seq..=to_seq {
+            let mark = chain_dir.read_mark(seq)?.mark().clone();
+            if let Some(previous) = marks.last()
+                && let Err(e) = previous.precedes_opt(&mark)
+            {
+                bail!(
+                    "Mark {} does not follow mark {}: {}",
+                    mark.seq(),
+                    previous.seq(),
+                    e
+                );
+            }
+            marks.push(mark);
+        }
+
+        Ok(bundle(&marks).ur_string())
+    }
+}
+
+/// Bundle a run of marks from one chain into an envelope whose subject is the
+/// chain ID.
+pub fn bundle(marks: &[ProvenanceMark]) -> Envelope {
+    let chain_id = marks[0].chain_id();
+    marks.iter().fold(
+        Envelope::new(CBOR::to_byte_string(chain_id)),
+        |envelope, mark| {
+            envelope.add_assertion(known_values::PROVENANCE, mark.clone())
+        },
+    )
+}
+
+/// Read the marks from a proof bundle in sequence order, checking that they
+/// all belong to the chain named by its subject.
+pub fn read_bundle(ur_string: &str) -> Result<Vec<ProvenanceMark>> {
+    let envelope = Envelope::from_ur_string(ur_string)
+        .map_err(|e| anyhow!("Failed to parse proof bundle: {}", e))?;
+    let chain_id = envelope
+        .subject()
+        .try_leaf()
+        .ok()
+        .and_then(|subject| subject.try_into_byte_string().ok())
+        .ok_or_else(|| anyhow!("Proof bundle's subject is not a chain ID"))?;
+
+    let mut marks = envelope
+        .objects_for_predicate(known_values::PROVENANCE)
+        .into_iter()
+        .map(|object| {
+            ProvenanceMark::try_from(object).map_err(|e| {
+                anyhow!("Failed to decode a mark in the proof bundle: {}", e)
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if marks.is_empty() {
+        bail!("Proof bundle contains no marks");
+    }
+    if marks
+        .iter()
+        .any(|mark| mark.chain_id() != chain_id.as_slice())
+    {
+        bail!("Proof bundle contains marks from another chain");
+    }
+
+    // Assertions are not kept in order, so restore sequence order.
+    marks.sort_by_key(|mark| mark.seq());
+    Ok(marks)
+}
@@ -0,0 +1,84 @@
+use anyhow::{Result, bail};
+use clap::Args;
+use dcbor::prelude::*;
+use provenance_mark::util::parse_date;
+
+use super::chain::ChainArgs;
+use crate::{
+    chain_dir::ChainDir,
+    config::UserConfig,
+    publications::{PUBLICATIONS_FILE, Publication, Publications},
+};
+
+/// Record that a mark has been published, and where.
+///
+/// Records are kept in the chain's `publications.json`. A published mark
+/// cannot be undone.
+#[derive(Debug, Args)]
+#[group(skip)]
+#[command(allow_missing_positional = true)]
+pub struct CommandArgs {
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    /// The sequence number of the published mark. Given alone, without a
+    /// path before it, the chain is the one named by `--chain` or
+    /// `PROVENANCE_CHAIN`.
+    #[arg(value_name = "SEQ")]
+    seq: u32,
+
+    /// Where the mark was published.
+    #[arg(long)]
+    url: String,
+
+    /// When the mark was published. If not supplied, the current date is
+    /// used.
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    at: Option<Date>,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let user = UserConfig::load()?;
+        let chain_dir = ChainDir::open(&self.chain.path(&user)?)?;
+        if !chain_dir.mark_path(self.seq).exists() {
+            bail!("The chain has no mark {}.", self.seq);
+        }
+
+        // A mark is recorded once for each place it is published.
+        let mut publications = Publications::load(chain_dir.path())?;
+        if let Some(existing) = publications.find(self.seq, &self.url) {
+            return Ok(format!(
+                "Mark {} is already recorded as published at {}{}.",
+                self.seq,
+                self.url,
+                existing
+                    .at
+                    .as_ref()
+                    .map(|at| format!(" on {}", at))
+                    .unwrap_or_default()
+            ));
+        }
+        let at = self.at.unwrap_or_else(Date::now).to_string();
+        publications.add(Publication {
+            seq: self.seq,
+            url: Some(self.url.clone()),
+            at: Some(at.clone()),
+        });
+        publications.save(chain_dir.path())?;
+        // Neither this mark nor any before it can be undone now.
+        if !chain_dir.is_mirror() {
+            chain_dir.prune_snapshots(
+                chain_dir.last_seq()?,
+                publications.last_published(),
+            )?;
+        }
+        Ok(format!(
+            "Mark {} recorded as published at {} on {}, in: {}",
+            self.seq,
+            self.url,
+            at,
+            chain_dir.path().join(PUBLICATIONS_FILE).display()
+        ))
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+
+use super::{chain::ChainArgs, metadata::ChainMetadata, new::Resolution};
+use crate::{
+    chain_dir::ChainDir, config::UserConfig, publications::Publications,
+};
+
+/// Summarize the state of a chain directory or mirror.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    /// List the marks that have not been recorded as published with
+    /// `publish-record`.
+    #[arg(long)]
+    unpublished: bool,
+
+    /// Output format for the summary.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let user = UserConfig::load()?;
+        let chain_dir = ChainDir::open(&self.chain.path(&user)?)?;
+        let kind = if chain_dir.is_mirror() {
+            "mirror"
+        } else {
+            "chain"
+        };
+        let seqs = chain_dir.mark_seqs()?;
+        let head = chain_dir.read_mark(chain_dir.last_seq()?)?;
+        let head = head.mark();
+        let missing: Vec<u32> = (0..=head.seq())
+            .filter(|seq| seqs.binary_search(seq).is_err())
+            .collect();
+        let resolution =
+            Resolution::from_provenance_mark_resolution(head.res());
+        let unpublished = if self.unpublished {
+            let publications = Publications::load(chain_dir.path())?;
+            Some(
+                seqs.iter()
+                    .copied()
+                    .filter(|seq| !publications.is_published(*seq))
+                    .collect::<Vec<u32>>(),
+            )
+        } else {
+            None
+        };
+        let metadata = if seqs.first() == Some(&0) {
+            ChainMetadata::from_mark(chain_dir.read_mark(0)?.mark())
+        } else {
+            None
+        };
+
+        match self.format {
+            Format::Text => {
+                let mut lines = vec![
+                    format!("Path: {}", chain_dir.path().display()),
+                    format!("Kind: {}", kind),
+                ];
+                lines.extend(metadata.iter().flat_map(ChainMetadata::lines));
+                lines.extend([
+                    format!("Chain ID: {}", hex::encode(head.chain_id())),
+                    format!("Resolution: {}", resolution),
+                    format!("Marks: {}", seqs.len()),
+                    format!(
+                        "Head: mark {} ({}), {}",
+                        head.seq(),
+                        head.bytewords_identifier(true),
+                        head.date()
+                    ),
+                ]);
+                if !missing.is_empty() {
+                    let missing: Vec<String> =
+                        missing.iter().map(u32::to_string).collect();
+                    lines
+                        .push(format!("Missing marks: {}", missing.join(", ")));
+                }
+                if let Some(unpublished) = &unpublished {
+                    let unpublished: Vec<String> =
+                        unpublished.iter().map(u32::to_string).collect();
+                    lines.push(format!(
+                        "Unpublished marks: {}",
+                        if unpublished.is_empty() {
+                            "none".to_string()
+                        } else {
+                            unpublished.join(", ")
+                        }
+                    ));
+                }
+                Ok(lines.join("\n"))
+            }
+            Format::Json => {
+                let mut json = serde_json::json!({
+                    "path": chain_dir.path().display().to_string(),
+                    "kind": kind,
+                    "metadata": metadata,
+                    "chain": hex::encode(head.chain_id()),
+                    "resolution": resolution.to_string(),
+                    "marks": seqs.len(),
+                    "missing": missing,
+                    "head": {
+                        "seq": head.seq(),
+                        "identifier": head.identifier(),
+                        "date": head.date().to_string(),
+                    },
+                });
+                if let Some(unpublished) = unpublished {
+                    json["unpublished"] = unpublished.into();
+                }
+                Ok(serde_json::to_string_pretty(&json)?)
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use bc_envelope::prelude::*;
+use provenance_mark::ProvenanceMark;
+
+use super::metadata::METADATA_SUBJECT;
+
+/// A reference from a chain's genesis mark to the head of the chain it
+/// succeeds, stored as assertions on the genesis mark's metadata envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Predecessor {
+    pub chain_id: Vec<u8>,
+    pub seq: u32,
+    pub hash: Vec<u8>,
+}
+
+impl Predecessor {
+    /// A reference to `head` as the last mark of the preceding chain.
+    pub fn of(head: &ProvenanceMark) -> Self {
+        Self {
+            chain_id: head.chain_id().to_vec(),
+            seq: head.seq(),
+            hash: head.hash().to_vec(),
+        }
+    }
+
+    pub fn add_to(&self, envelope: Envelope) -> Envelope {
+        envelope
+            .add_assertion(
+                "predecessorChain",
+                CBOR::to_byte_string(&self.chain_id),
+            )
+            .add_assertion("predecessorSeq", self.seq)
+            .add_assertion("predecessorHash", CBOR::to_byte_string(&self.hash))
+    }
+
+    /// The predecessor named by a genesis mark's `info`, if any.
+    pub fn from_mark(mark: &ProvenanceMark) -> Option<Self> {
+        if !mark.is_genesis() {
+            return None;
+        }
+        let envelope = Envelope::try_from(mark.info()?).ok()?;
+        if envelope.extract_subject::<String>().ok()? != METADATA_SUBJECT {
+            return None;
+        }
+        Some(Self {
+            chain_id: byte_string(&envelope, "predecessorChain")?,
+            seq: envelope
+                .extract_object_for_predicate("predecessorSeq")
+                .ok()?,
+            hash: byte_string(&envelope, "predecessorHash")?,
+        })
+    }
+
+    pub fn matches(&self, mark: &ProvenanceMark) -> bool {
+        mark.chain_id() == self.chain_id.as_slice()
+            && mark.seq() == self.seq
+            && mark.hash() == self.hash.as_slice()
+    }
+}
+
+/// A commitment in a chain's closing mark to the genesis mark of the chain
+/// that succeeds it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Successor {
+    pub chain_id: Vec<u8>,
+    pub genesis_hash: Vec<u8>,
+}
+
+impl Successor {
+    pub fn of(genesis: &ProvenanceMark) -> Self {
+        Self {
+            chain_id: genesis.chain_id().to_vec(),
+            genesis_hash: genesis.hash().to_vec(),
+        }
+    }
+
+    pub fn add_to(&self, envelope: Envelope) -> Envelope {
+        envelope
+            .add_assertion(
+                "successorChain",
+                CBOR::to_byte_string(&self.chain_id),
+            )
+            .add_assertion(
+                "successorGenesisHash",
+                CBOR::to_byte_string(&self.genesis_hash),
+            )
+    }
+
+    pub fn from_envelope(envelope: &Envelope) -> Option<Self> {
+        Some(Self {
+            chain_id: byte_string(envelope, "successorChain")?,
+            genesis_hash: byte_string(envelope, "successorGenesisHash")?,
+        })
+    }
+
+    pub fn matches(&self, mark: &ProvenanceMark) -> bool {
+        mark.is_genesis()
+            && mark.chain_id() == self.chain_id.as_slice()
+            && mark.hash() == self.genesis_hash.as_slice()
+    }
+}
+
+fn byte_string(envelope: &Envelope, predicate: &str) -> Option<Vec<u8>> {
+    envelope
+        .object_for_predicate(predicate)
+        .ok()?
+        .try_leaf()
+        .ok()?
+        .try_into_byte_string()
+        .ok()
+}
@@ -0,0 +1,104 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::{Result, bail};
+use clap::Args;
+
+use super::chain::{ChainArgs, GeneratorArgs};
+use crate::{
+    chain_dir::ChainDir, config::UserConfig, idempotency::IdempotencyKeys,
+    publications::Publications,
+};
+
+/// Remove the most recent mark of a chain and restore the generator to its
+/// state before that mark was generated.
+///
+/// Only a mark made by `next` that has never been published can be undone.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    #[command(flatten)]
+    chain: ChainArgs,
+
+    #[command(flatten)]
+    generator: GeneratorArgs,
+
+    /// Undo the mark without asking for confirmation.
+    #[arg(short, long)]
+    force: bool,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let user = UserConfig::load()?;
+        let chain_dir = ChainDir::open(&self.chain.path(&user)?)?
+            .with_generator(self.generator.path())?;
+        if chain_dir.is_mirror() {
+            bail!(
+                "{} is a mirror; it has no generator to restore.",
+                chain_dir.path().display()
+            );
+        }
+        if chain_dir.is_closed()? {
+            bail!("The chain is closed; its closing mark cannot be undone.");
+        }
+
+        let generator = chain_dir.read_generator()?;
+        let seq = generator.next_seq() - 1;
+        if seq == 0 {
+            bail!(
+                "Mark 0 is the genesis mark; delete the chain's directory \
+                 instead."
+            );
+        }
+        if Publications::load(chain_dir.path())?.is_published(seq) {
+            bail!("Mark {} has been published; it cannot be undone.", seq);
+        }
+        let snapshot = chain_dir.read_snapshot(seq)?;
+        if snapshot.chain_id() != generator.chain_id()
+            || snapshot.next_seq() != seq
+        {
+            bail!(
+                "The generator snapshot from before mark {} is invalid.",
+                seq
+            );
+        }
+        let mark_info = chain_dir.read_mark(seq)?;
+
+        if !self.force
+            && !confirm(&format!(
+                "Remove mark {} ({}) and restore the generator?",
+                seq,
+                mark_info.comment()
+            ))?
+        {
+            bail!("Undo cancelled; the chain is unchanged.");
+        }
+
+        // Restore the generator first, so that an interruption leaves the
+        // mark to be generated again rather than a generator that has moved
+        // past a missing mark.
+        chain_dir.write_generator(&snapshot)?;
+        chain_dir.remove_mark(seq)?;
+        chain_dir.remove_snapshot(seq)?;
+
+        // A retried request for the removed mark gets a new one.
+        let mut keys = IdempotencyKeys::load(chain_dir.path())?;
+        if keys.forget(seq) {
+            keys.save(chain_dir.path())?;
+        }
+
+        Ok(format!(
+            "Mark {} removed; the next mark generated will be mark {}.",
+            seq, seq
+        ))
+    }
+}
+
+/// Ask a yes-or-no question on stderr, reading the answer from stdin.
+fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
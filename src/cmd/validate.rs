@@ -1,31 +1,131 @@
-use std::{fs, path::PathBuf};
+mod class;
+mod closure;
+mod compromise;
+mod directory;
+mod explain;
+mod freshness;
+mod junit;
+mod policy;
+mod recursive;
+mod sarif;
+mod stream;
+mod succession;
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use anyhow::{Result, bail};
 use bc_envelope::prelude::*;
-use bc_ur::UR;
 use clap::{Args, ValueEnum};
 use provenance_mark::{
-    ProvenanceMark, ProvenanceMarkInfo, ValidationReportFormat,
+    ProvenanceMark, ValidationReport, ValidationReportFormat, util::parse_date,
 };
 
-use crate::utils::read_existing_directory_path;
+use self::{
+    class::{Finding, IssueClass, Source},
+    freshness::Freshness,
+    policy::Policy,
+    succession::HistoryEntry,
+};
+use crate::{
+    cmd::{
+        chain::ChainArgs, extract, heartbeat::is_heartbeat,
+        metadata::ChainMetadata,
+    },
+    config::{CHAIN_ENV_VAR, UserConfig},
+    exec::ExitError,
+    trust_store::TrustStore,
+    utils::{parse_duration, read_existing_directory_path},
+};
 
 /// Validate one or more provenance marks.
-#[derive(Debug, Args)]
+///
+/// If no marks are given, the chain named by the `PROVENANCE_CHAIN`
+/// environment variable is validated as with `--dir`.
+#[derive(Debug, Clone, Args)]
 #[group(skip)]
 pub struct CommandArgs {
     /// One or more provenance mark URs to validate.
-    #[arg(required_unless_present = "dir")]
     marks: Vec<String>,
 
-    /// Path to a chain directory containing marks to validate.
-    #[arg(short, long, conflicts_with = "marks")]
+    /// Path to a chain directory containing marks to validate. The chains
+    /// it succeeds, as recorded in each `chain.toml`, are validated with it.
+    #[arg(short, long, conflicts_with_all = ["marks", "files"])]
     dir: Option<PathBuf>,
 
+    /// The name of a chain listed in the user's `config.toml`, validated as
+    /// with `--dir`.
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["marks", "dir", "recursive", "files"]
+    )]
+    chain: Option<String>,
+
+    /// Path to a directory to search for chain directories, each of which is
+    /// validated on its own.
+    #[arg(
+        short,
+        long,
+        value_name = "ROOT",
+        conflicts_with_all = ["marks", "dir", "files"]
+    )]
+    recursive: Option<PathBuf>,
+
+    /// Path to a document to scan for provenance mark URs, such as a
+    /// published list of marks. May be given more than once.
+    #[arg(short, long = "file", value_name = "PATH")]
+    files: Vec<PathBuf>,
+
     /// Report issues as warnings without failing.
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with_all = ["fail_on", "allow"])]
     warn: bool,
 
+    /// Only fail on these classes of issue. If not supplied, every class is
+    /// fatal.
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "CLASS")]
+    fail_on: Vec<IssueClass>,
+
+    /// Report these classes of issue as warnings without failing.
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "CLASS")]
+    allow: Vec<IssueClass>,
+
+    /// Path to a JSON policy file whose rules are checked in addition to
+    /// chain integrity.
+    #[arg(long, value_name = "PATH")]
+    policy: Option<PathBuf>,
+
+    /// Report a chain as stale if its newest mark is older than this, for
+    /// example `30d` or `1w2d`.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    max_age: Option<Duration>,
+
+    /// Report a silence wherever consecutive marks are further apart in time
+    /// than this.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    max_interval: Option<Duration>,
+
+    /// The date that `--max-age` is measured against. If not supplied, the
+    /// current date is used.
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    now: Option<Date>,
+
+    /// Explain each issue in plain language: what it means, how serious it
+    /// is, and what to do about it.
+    #[arg(long)]
+    explain: bool,
+
+    /// Check marks as they are read and write each issue as soon as it is
+    /// found, instead of building a full report. Suited to very long chains;
+    /// each chain's marks must be given in sequence order.
+    #[arg(long, conflicts_with_all = ["recursive", "policy"])]
+    stream: bool,
+
     /// Output format for the validation report.
     #[arg(long, value_enum, default_value_t = Format::Text)]
     format: Format,
@@ -36,212 +136,523 @@ enum Format {
     Text,
     JsonCompact,
     JsonPretty,
+    /// JUnit XML, with one test suite per chain.
+    Junit,
+    /// SARIF 2.1.0, with one result per issue.
+    Sarif,
 }
 
 impl Format {
-    fn as_validation_report_format(&self) -> ValidationReportFormat {
+    fn as_validation_report_format(&self) -> Option<ValidationReportFormat> {
         match self {
-            Format::Text => ValidationReportFormat::Text,
-            Format::JsonCompact => ValidationReportFormat::JsonCompact,
-            Format::JsonPretty => ValidationReportFormat::JsonPretty,
+            Format::Text => Some(ValidationReportFormat::Text),
+            Format::JsonCompact => Some(ValidationReportFormat::JsonCompact),
+            Format::JsonPretty => Some(ValidationReportFormat::JsonPretty),
+            Format::Junit | Format::Sarif => None,
         }
     }
 }
 
+/// The outcome of validating one set of marks.
+#[derive(Debug)]
+struct Checked {
+    /// The chain directory the marks were read from, when validating
+    /// recursively.
+    dir: Option<PathBuf>,
+    report: ValidationReport,
+    findings: Vec<Finding>,
+    sources: HashMap<Vec<u8>, Source>,
+    /// The chains in order, when they succeed one another.
+    history: Vec<HistoryEntry>,
+}
+
+/// The marks gathered for validation, with the file each was read from and
+/// any inputs that could not be parsed.
+#[derive(Debug, Default)]
+struct Inputs {
+    marks: Vec<ProvenanceMark>,
+    sources: HashMap<Vec<u8>, Source>,
+    findings: Vec<Finding>,
+}
+
+impl Inputs {
+    fn add_mark(&mut self, mark: ProvenanceMark, source: Option<Source>) {
+        if let Some(source) = source {
+            self.sources.entry(mark.hash().to_vec()).or_insert(source);
+        }
+        self.marks.push(mark);
+    }
+
+    fn add_parse_error(&mut self, message: String, source: Option<Source>) {
+        self.findings
+            .push(Finding::new(IssueClass::Parse, message).at_source(source));
+    }
+}
+
 impl crate::exec::Exec for CommandArgs {
     fn exec(&self) -> Result<String> {
-        // Collect marks from either URs or directory
-        let marks = if let Some(dir_path) = &self.dir {
-            self.load_marks_from_dir(dir_path)?
+        if let Some(args) = self.with_named_chain()? {
+            return args.exec();
+        }
+        if self.stream {
+            let mut output = Vec::new();
+            self.exec_to(&mut output)?;
+            let mut output = String::from_utf8(output)?;
+            if output.ends_with('\n') {
+                output.pop();
+            }
+            return Ok(output);
+        }
+
+        let policy = self.policy.as_deref().map(Policy::load).transpose()?;
+        let freshness = self.freshness();
+        let trust = load_trust_store();
+
+        // Validate every chain directory under the root in parallel.
+        if let Some(root) = &self.recursive {
+            let root = read_existing_directory_path(root)?;
+            let dirs = recursive::find_chain_dirs(&root)?;
+            let checked = recursive::check_all(&dirs, |dir| {
+                let mut checked = self.check(
+                    directory::load_all(dir),
+                    policy.as_ref(),
+                    &freshness,
+                    &trust,
+                );
+                checked.dir = Some(dir.to_path_buf());
+                checked
+            });
+            let output = match self.format {
+                Format::Text => recursive::render_text(self, &root, &checked),
+                Format::JsonCompact | Format::JsonPretty => {
+                    recursive::render_json(self, &root, &checked)?
+                }
+                Format::Junit | Format::Sarif => {
+                    self.render_for_ci(&checked)?
+                }
+            };
+            return self.finish(&checked, output);
+        }
+
+        // Collect marks from either URs or directory. Inputs that cannot be
+        // parsed are recorded as findings rather than ending validation.
+        let mut inputs = Inputs::default();
+        if let Some(dir_path) = &self.dir {
+            let path = read_existing_directory_path(dir_path)?;
+            directory::load(&path, &mut inputs)?;
+            succession::load_predecessors(&path, &mut inputs);
         } else {
-            self.parse_marks_from_urs(&self.marks)?
-        };
+            self.parse_marks_from_urs(&self.marks, &mut inputs);
+            for file in &self.files {
+                self.scan_marks_from_file(file, &mut inputs)?;
+            }
+        }
 
         // Validate the marks
-        let report = ProvenanceMark::validate(marks);
+        let checked = self.check(inputs, policy.as_ref(), &freshness, &trust);
 
         // Format the output
-        let output = report.format(self.format.as_validation_report_format());
+        let output = match self.format.as_validation_report_format() {
+            Some(format) => self.format_output(
+                checked.report.format(format),
+                &checked.findings,
+                &chain_metadata(&checked.report),
+                &checked.history,
+                &heartbeats(&checked.report),
+            )?,
+            None => self.render_for_ci(std::slice::from_ref(&checked))?,
+        };
+
+        self.finish(std::slice::from_ref(&checked), output)
+    }
 
-        // Determine if we should fail
-        if report.has_issues() && !self.warn {
-            bail!("Validation failed with issues:\n{}", output);
+    fn exec_to(&self, out: &mut dyn Write) -> Result<()> {
+        if let Some(args) = self.with_named_chain()? {
+            return args.exec_to(out);
+        }
+        if !self.stream {
+            let output = self.exec()?;
+            if !output.is_empty() {
+                writeln!(out, "{}", output)?;
+            }
+            return Ok(());
         }
 
-        Ok(output)
+        let trust = load_trust_store();
+        if let Some(class) = stream::run(self, &self.freshness(), &trust, out)?
+        {
+            return Err(ExitError::new(
+                class.exit_code(),
+                "Validation failed with issues",
+            )
+            .into());
+        }
+        Ok(())
     }
 }
 
 impl CommandArgs {
-    fn parse_marks_from_urs(
-        &self,
-        ur_strings: &[String],
-    ) -> Result<Vec<ProvenanceMark>> {
-        let mut marks = Vec::new();
-        for ur_string in ur_strings {
-            let mark = self.extract_provenance_mark(ur_string.trim())?;
-            marks.push(mark);
+    /// These arguments with `--dir` set to the named chain's directory, when
+    /// a chain is named by `--chain`, or by `PROVENANCE_CHAIN` because
+    /// nothing else was given to validate.
+    fn with_named_chain(&self) -> Result<Option<Self>> {
+        if self.chain.is_none() {
+            if self.dir.is_some()
+                || self.recursive.is_some()
+                || !self.marks.is_empty()
+                || !self.files.is_empty()
+            {
+                return Ok(None);
+            }
+            if env::var_os(CHAIN_ENV_VAR).is_none_or(|name| name.is_empty()) {
+                bail!(
+                    "Nothing to validate; supply marks, `--dir`, `--file`, \
+                     `--recursive`, `--chain <NAME>`, or set {}",
+                    CHAIN_ENV_VAR
+                );
+            }
+        }
+        let chain = ChainArgs {
+            path: None,
+            chain: self.chain.clone(),
+        };
+        Ok(Some(Self {
+            dir: Some(chain.path(&UserConfig::load()?)?),
+            chain: None,
+            ..self.clone()
+        }))
+    }
+
+    fn freshness(&self) -> Freshness {
+        Freshness {
+            max_age: self.max_age,
+            max_interval: self.max_interval,
+            now: self.now.unwrap_or_else(Date::now),
         }
-        Ok(marks)
     }
 
-    /// Extract a ProvenanceMark from a UR string.
-    ///
-    /// Supports three types of URs:
-    /// 1. `ur:provenance` - Direct provenance mark
-    /// 2. `ur:envelope` - Envelope with a 'provenance' assertion
-    /// 3. Any other UR type - Attempts to decode CBOR as an envelope
-    fn extract_provenance_mark(
+    /// Validate the marks and classify every issue found.
+    fn check(
         &self,
-        ur_string: &str,
-    ) -> Result<ProvenanceMark> {
-        // Parse the UR to get its type and CBOR
-        let ur = UR::from_ur_string(ur_string).map_err(|e| {
-            anyhow::anyhow!("Failed to parse UR '{}': {}", ur_string, e)
-        })?;
+        inputs: Inputs,
+        policy: Option<&Policy>,
+        freshness: &Freshness,
+        trust: &TrustStore,
+    ) -> Checked {
+        let report = ProvenanceMark::validate(inputs.marks);
+        let mut findings = inputs.findings;
+        findings.extend(class::report_findings(&report));
+        if let Some(policy) = policy {
+            findings.extend(policy.check(&report));
+        }
+        findings.extend(freshness.check(&report));
+        findings.extend(closure::check(&report));
+        findings.extend(compromise::check(&report, trust));
+        // Chains that succeed one another form one history rather than
+        // unrelated chains.
+        let succession = succession::check(&report);
+        findings.extend(succession.findings);
+        if !succession.history.is_empty() {
+            findings
+                .retain(|finding| finding.class != IssueClass::MultipleChains);
+        }
+        for finding in &mut findings {
+            if finding.source.is_none()
+                && let Some(mark) = &finding.mark
+            {
+                finding.source = inputs.sources.get(mark.hash()).cloned();
+            }
+        }
+        Checked {
+            dir: None,
+            report,
+            findings,
+            sources: inputs.sources,
+            history: succession.history,
+        }
+    }
 
-        let ur_type = ur.ur_type_str();
-        let cbor = ur.cbor();
-
-        // Case 1: Direct provenance mark
-        // URs don't include the CBOR tag in their encoded format, so we use
-        // from_untagged_cbor
-        if ur_type == "provenance" {
-            return ProvenanceMark::from_untagged_cbor(cbor).map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to decode provenance mark from '{}': {}",
-                    ur_string,
-                    e
+    fn render_for_ci(&self, checked: &[Checked]) -> Result<String> {
+        let is_fatal = |class| self.is_fatal(class);
+        if self.format == Format::Junit {
+            Ok(junit::render(checked, &is_fatal))
+        } else {
+            let findings = checked.iter().flat_map(|c| &c.findings);
+            sarif::render(findings, &is_fatal)
+        }
+    }
+
+    /// Fail with the exit code of the most severe fatal class, if any.
+    fn finish(&self, checked: &[Checked], output: String) -> Result<String> {
+        let fatal = IssueClass::most_severe(
+            checked
+                .iter()
+                .flat_map(|checked| &checked.findings)
+                .map(|finding| finding.class)
+                .filter(|class| self.is_fatal(*class)),
+        );
+        if let Some(class) = fatal {
+            // Machine-readable reports are still written to stdout so that
+            // CI tools can pick them up.
+            let error = match self.format {
+                Format::Junit | Format::Sarif => ExitError::new(
+                    class.exit_code(),
+                    "Validation failed with issues",
                 )
-            });
+                .with_output(output),
+                _ => ExitError::new(
+                    class.exit_code(),
+                    format!("Validation failed with issues:\n{}", output),
+                ),
+            };
+            return Err(error.into());
         }
 
-        // Case 2 & 3: Try to decode CBOR as an envelope
-        let envelope = Envelope::from_untagged_cbor(cbor.clone()).map_err(|e| {
-            anyhow::anyhow!(
-                "UR type '{}' is not 'provenance', and CBOR is not decodable as an envelope: {}",
-                ur_type,
-                e
-            )
-        })?;
+        Ok(output)
+    }
+
+    fn is_fatal(&self, class: IssueClass) -> bool {
+        !self.warn
+            && (self.fail_on.is_empty() || self.fail_on.contains(&class))
+            && !self.allow.contains(&class)
+    }
 
-        // Extract the provenance mark from the envelope
-        self.extract_provenance_mark_from_envelope(&envelope, ur_string)
+    fn explain(&self, finding: &Finding, dir: Option<&Path>) -> String {
+        explain::explain(finding, self.is_fatal(finding.class), dir)
     }
 
-    /// Extract a ProvenanceMark from an Envelope.
-    ///
-    /// The envelope must contain exactly one 'provenance' assertion (possibly
-    /// inside one or more wrapper layers), and the object subject of that
-    /// assertion must be a ProvenanceMark.
-    fn extract_provenance_mark_from_envelope(
+    /// Add the findings that the library report does not show itself, the
+    /// names of chains whose genesis marks hold metadata, the order of chains
+    /// that succeed one another, and which marks are heartbeats.
+    fn format_output(
         &self,
-        envelope: &Envelope,
-        ur_string: &str,
-    ) -> Result<ProvenanceMark> {
-        let mut current = envelope.clone();
-
-        loop {
-            // Find all assertions with the 'provenance' predicate at this
-            // level.
-            let provenance_assertions =
-                current.assertions_with_predicate(known_values::PROVENANCE);
-
-            // Verify exactly one provenance assertion exists.
-            if provenance_assertions.len() > 1 {
-                bail!(
-                    "Envelope in '{}' contains {} 'provenance' assertions, expected exactly one",
-                    ur_string,
-                    provenance_assertions.len()
-                );
+        report_output: String,
+        findings: &[Finding],
+        metadata: &[(String, ChainMetadata)],
+        history: &[HistoryEntry],
+        heartbeats: &[(String, Vec<u32>)],
+    ) -> Result<String> {
+        match self.format {
+            Format::Text => {
+                let lines: Vec<String> = findings
+                    .iter()
+                    .filter(|finding| !finding.class.is_shown_in_report())
+                    .map(|finding| {
+                        format!("  {}: {}", finding.label(), finding.describe())
+                    })
+                    .collect();
+                let mut paragraphs = Vec::new();
+                if !report_output.is_empty() {
+                    paragraphs.push(report_output);
+                }
+                let names: Vec<String> = metadata
+                    .iter()
+                    .filter_map(|(chain_id, metadata)| {
+                        let name = metadata.name.as_ref()?;
+                        Some(format!("  {}: {}", chain_id, name))
+                    })
+                    .collect();
+                if !names.is_empty() {
+                    paragraphs
+                        .push(format!("Chain names:\n{}", names.join("\n")));
+                }
+                if !history.is_empty() {
+                    let entries: Vec<String> = history
+                        .iter()
+                        .enumerate()
+                        .map(|(index, entry)| {
+                            format!(
+                                "  {}. {}: marks {} through {}",
+                                index + 1,
+                                entry.chain_id,
+                                entry.first,
+                                entry.last
+                            )
+                        })
+                        .collect();
+                    paragraphs
+                        .push(format!("History:\n{}", entries.join("\n")));
+                }
+                if !heartbeats.is_empty() {
+                    let entries: Vec<String> = heartbeats
+                        .iter()
+                        .map(|(chain_id, seqs)| {
+                            let seqs: Vec<String> =
+                                seqs.iter().map(u32::to_string).collect();
+                            format!("  {}: {}", chain_id, seqs.join(", "))
+                        })
+                        .collect();
+                    paragraphs.push(format!(
+                        "Heartbeat marks:\n{}",
+                        entries.join("\n")
+                    ));
+                }
+                if !lines.is_empty() {
+                    paragraphs.push(format!("Issues:\n{}", lines.join("\n")));
+                }
+                if self.explain && !findings.is_empty() {
+                    let explanations: Vec<String> = findings
+                        .iter()
+                        .map(|finding| {
+                            self.explain(finding, self.dir.as_deref())
+                        })
+                        .collect();
+                    paragraphs.push(format!(
+                        "Explanations:\n\n{}",
+                        explanations.join("\n\n")
+                    ));
+                }
+                Ok(paragraphs.join("\n\n"))
             }
-            if let Some(provenance_assertion) = provenance_assertions.first() {
-                // Get the object of the provenance assertion.
-                let object_envelope = provenance_assertion.as_object()
-                    .ok_or_else(|| anyhow::anyhow!(
-                        "Failed to extract object from provenance assertion in '{}'",
-                        ur_string
-                    ))?;
-
-                // The object should be decodable as a ProvenanceMark.
-                // ProvenanceMark::try_from(Envelope) will extract the subject
-                // and decode it.
-                return ProvenanceMark::try_from(object_envelope).map_err(|e| anyhow::anyhow!(
-                    "Failed to decode ProvenanceMark from provenance assertion in '{}': {}",
-                    ur_string,
-                    e
-                ));
+            _ => {
+                let mut json: serde_json::Value =
+                    serde_json::from_str(&report_output)?;
+                if let Some(object) = json.as_object_mut() {
+                    let issues = findings.iter().map(|finding| {
+                        let mut issue = finding.to_json();
+                        if self.explain {
+                            issue["explanation"] = self
+                                .explain(finding, self.dir.as_deref())
+                                .into();
+                        }
+                        issue
+                    });
+                    object.insert("issues".to_string(), issues.collect());
+                    if !metadata.is_empty() {
+                        let metadata: serde_json::Map<_, _> = metadata
+                            .iter()
+                            .map(|(chain_id, metadata)| {
+                                Ok((
+                                    chain_id.clone(),
+                                    serde_json::to_value(metadata)?,
+                                ))
+                            })
+                            .collect::<Result<_>>()?;
+                        object.insert(
+                            "chainMetadata".to_string(),
+                            metadata.into(),
+                        );
+                    }
+                    if !history.is_empty() {
+                        object.insert(
+                            "history".to_string(),
+                            serde_json::to_value(history)?,
+                        );
+                    }
+                    if !heartbeats.is_empty() {
+                        let heartbeats: serde_json::Map<_, _> = heartbeats
+                            .iter()
+                            .map(|(chain_id, seqs)| {
+                                (chain_id.clone(), seqs.clone().into())
+                            })
+                            .collect();
+                        object.insert(
+                            "heartbeats".to_string(),
+                            heartbeats.into(),
+                        );
+                    }
+                }
+                if self.format == Format::JsonPretty {
+                    Ok(serde_json::to_string_pretty(&json)?)
+                } else {
+                    Ok(serde_json::to_string(&json)?)
+                }
             }
+        }
+    }
 
-            // No provenance at this level. If this is a wrapper around a
-            // signed/content envelope, strip one layer and try again.
-            match current.try_unwrap() {
-                Ok(unwrapped) => current = unwrapped,
-                Err(_) => {
-                    bail!(
-                        "Envelope in '{}' does not contain a 'provenance' assertion",
-                        ur_string
-                    );
-                }
+    fn parse_marks_from_urs(&self, ur_strings: &[String], inputs: &mut Inputs) {
+        for ur_string in ur_strings {
+            match extract::provenance_mark_from_ur(ur_string.trim()) {
+                Ok(mark) => inputs.add_mark(mark, None),
+                Err(e) => inputs.add_parse_error(e.to_string(), None),
             }
         }
     }
 
-    fn load_marks_from_dir(
+    /// Scan a document line by line for provenance mark URs, remembering the
+    /// line each was found on.
+    fn scan_marks_from_file(
         &self,
-        dir_path: &PathBuf,
-    ) -> Result<Vec<ProvenanceMark>> {
-        // Get the chain's directory path
-        let path = read_existing_directory_path(dir_path)?;
-
-        // Get the marks subdirectory
-        let marks_path = path.join("marks");
-        if !marks_path.exists() || !marks_path.is_dir() {
-            bail!("Marks subdirectory not found: {}", marks_path.display());
-        }
-
-        // Read all JSON files from the marks directory
-        let entries = fs::read_dir(&marks_path)?;
-        let mut mark_files: Vec<_> = entries
-            .filter_map(|entry| {
-                entry.ok().and_then(|e| {
-                    let path = e.path();
-                    if path.extension().and_then(|s| s.to_str()) == Some("json")
-                    {
-                        Some(path)
-                    } else {
-                        None
-                    }
-                })
-            })
-            .collect();
-
-        // Sort the files by name to ensure proper ordering
-        mark_files.sort();
+        path: &Path,
+        inputs: &mut Inputs,
+    ) -> Result<()> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("Failed to read {}: {}", path.display(), e)
+        })?;
 
-        if mark_files.is_empty() {
-            bail!("No mark JSON files found in: {}", marks_path.display());
+        for (index, line) in content.lines().enumerate() {
+            for token in ur_tokens(line) {
+                let source = Some(Source {
+                    path: path.to_path_buf(),
+                    line: Some(index + 1),
+                });
+                match extract::provenance_mark_from_ur(token) {
+                    Ok(mark) => inputs.add_mark(mark, source),
+                    Err(e) => inputs.add_parse_error(e.to_string(), source),
+                }
+            }
         }
+        Ok(())
+    }
+}
 
-        // Parse each JSON file and extract the mark
-        let mut marks = Vec::new();
-        for mark_file in mark_files {
-            let json_content = fs::read_to_string(&mark_file).map_err(|e| {
-                anyhow::anyhow!("Failed to read {}: {}", mark_file.display(), e)
-            })?;
+/// Find the UR-like tokens in a line of text, ignoring any surrounding quotes,
+/// backticks, or punctuation.
+fn ur_tokens(line: &str) -> impl Iterator<Item = &str> {
+    line.split_whitespace()
+        .map(|token| {
+            token.trim_matches(|c: char| {
+                !c.is_ascii_alphanumeric() && c != ':' && c != '/'
+            })
+        })
+        .filter(|token| {
+            token
+                .get(..3)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("ur:"))
+        })
+}
 
-            let mark_info: ProvenanceMarkInfo =
-                serde_json::from_str(&json_content).map_err(|e| {
-                    anyhow::anyhow!(
-                        "Failed to parse JSON from {}: {}",
-                        mark_file.display(),
-                        e
-                    )
-                })?;
+/// Load the trust store of compromise notices, warning on stderr about any
+/// notice that could not be read rather than failing.
+fn load_trust_store() -> TrustStore {
+    let trust = TrustStore::load();
+    for problem in trust.problems() {
+        eprintln!("Warning: skipped compromise notice {}", problem);
+    }
+    trust
+}
 
-            marks.push(mark_info.mark().clone());
-        }
+/// The sequence numbers of the heartbeat marks of each chain that has any.
+fn heartbeats(report: &ValidationReport) -> Vec<(String, Vec<u32>)> {
+    report
+        .chains()
+        .iter()
+        .filter_map(|chain| {
+            let mut seqs: Vec<u32> = chain
+                .marks()
+                .iter()
+                .filter(|mark| is_heartbeat(mark))
+                .map(|mark| mark.seq())
+                .collect();
+            if seqs.is_empty() {
+                return None;
+            }
+            seqs.sort();
+            Some((chain.chain_id_hex(), seqs))
+        })
+        .collect()
+}
 
-        Ok(marks)
-    }
+/// The metadata held by each chain's genesis mark, by chain ID.
+fn chain_metadata(report: &ValidationReport) -> Vec<(String, ChainMetadata)> {
+    report
+        .chains()
+        .iter()
+        .filter_map(|chain| {
+            let metadata =
+                chain.marks().iter().find_map(ChainMetadata::from_mark)?;
+            Some((chain.chain_id_hex(), metadata))
+        })
+        .collect()
 }
@@ -0,0 +1,357 @@
+use std::{fmt, ops::RangeInclusive, path::PathBuf};
+
+use clap::ValueEnum;
+use provenance_mark::{ProvenanceMark, ValidationIssue, ValidationReport};
+
+/// A class of validation issue. Each class has its own documented exit code
+/// so that pipelines can tell outcomes apart.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, ValueEnum)]
+pub enum IssueClass {
+    /// An input could not be parsed as a provenance mark.
+    Parse,
+    /// The marks belong to more than one chain.
+    MultipleChains,
+    /// Marks are missing between two marks of a chain.
+    Gap,
+    /// A chain has no genesis mark.
+    MissingGenesis,
+    /// A mark is dated before its predecessor.
+    DateOrder,
+    /// A mark's hash or key does not link it to its predecessor.
+    Mismatch,
+    /// Two different marks share a sequence number in the same chain.
+    Fork,
+    /// A rule of the validation policy is violated.
+    Policy,
+    /// The newest mark of a chain is older than `--max-age`.
+    Stale,
+    /// Consecutive marks are further apart in time than `--max-interval`.
+    Silence,
+    /// A chain directory's files disagree with the marks they hold.
+    Directory,
+    /// A mark comes after the mark that closed its chain.
+    AfterClose,
+    /// A chain's link to the chain it succeeds does not hold.
+    Succession,
+    /// A mark comes after the cutoff of a compromise notice in the trust
+    /// store.
+    Untrusted,
+}
+
+impl IssueClass {
+    /// The process exit code used when this class causes validation to fail.
+    /// When several fatal classes are present, the code of the most severe
+    /// is used.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            IssueClass::Parse => 3,
+            IssueClass::MultipleChains => 4,
+            IssueClass::Gap => 5,
+            IssueClass::MissingGenesis => 6,
+            IssueClass::DateOrder => 7,
+            IssueClass::Mismatch => 8,
+            IssueClass::Fork => 9,
+            IssueClass::Policy => 10,
+            IssueClass::Stale => 11,
+            IssueClass::Silence => 12,
+            IssueClass::Directory => 13,
+            IssueClass::AfterClose => 14,
+            IssueClass::Succession => 15,
+            IssueClass::Untrusted => 16,
+        }
+    }
+
+    /// How severe the class is, from `parse` (0) to `untrusted` (13). Classes
+    /// that suggest a compromised or copied generator outrank those that only
+    /// show marks missing or a chain gone quiet, whatever their exit codes.
+    pub fn severity(&self) -> u8 {
+        match self {
+            IssueClass::Parse => 0,
+            IssueClass::MultipleChains => 1,
+            IssueClass::Gap => 2,
+            IssueClass::MissingGenesis => 3,
+            IssueClass::Silence => 4,
+            IssueClass::Stale => 5,
+            IssueClass::Policy => 6,
+            IssueClass::Directory => 7,
+            IssueClass::DateOrder => 8,
+            IssueClass::Succession => 9,
+            IssueClass::AfterClose => 10,
+            IssueClass::Mismatch => 11,
+            IssueClass::Fork => 12,
+            IssueClass::Untrusted => 13,
+        }
+    }
+
+    /// The most severe of some classes, whose exit code is returned when they
+    /// are all fatal.
+    pub fn most_severe(
+        classes: impl IntoIterator<Item = IssueClass>,
+    ) -> Option<IssueClass> {
+        classes.into_iter().max_by_key(IssueClass::severity)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IssueClass::Parse => "parse",
+            IssueClass::MultipleChains => "multiple-chains",
+            IssueClass::Gap => "gap",
+            IssueClass::MissingGenesis => "missing-genesis",
+            IssueClass::DateOrder => "date-order",
+            IssueClass::Mismatch => "mismatch",
+            IssueClass::Fork => "fork",
+            IssueClass::Policy => "policy",
+            IssueClass::Stale => "stale",
+            IssueClass::Silence => "silence",
+            IssueClass::Directory => "directory",
+            IssueClass::AfterClose => "after-close",
+            IssueClass::Succession => "succession",
+            IssueClass::Untrusted => "untrusted",
+        }
+    }
+
+    /// A one-line description of the class.
+    pub fn description(&self) -> String {
+        self.to_possible_value()
+            .and_then(|value| value.get_help().map(|help| help.to_string()))
+            .unwrap_or_default()
+    }
+
+    /// Whether the library's validation report already shows issues of this
+    /// class, so they need not be listed again in the text output.
+    pub fn is_shown_in_report(&self) -> bool {
+        matches!(
+            self,
+            IssueClass::MultipleChains
+                | IssueClass::Gap
+                | IssueClass::MissingGenesis
+                | IssueClass::DateOrder
+                | IssueClass::Mismatch
+        )
+    }
+}
+
+impl fmt::Display for IssueClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The file, and line within it, that a mark or issue was read from.
+#[derive(Clone, Debug)]
+pub struct Source {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}", self.path.display(), line),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+/// A single classified validation issue.
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub class: IssueClass,
+    /// The hex chain ID the issue belongs to, if any.
+    pub chain_id: Option<String>,
+    /// The mark the issue was found on, if any.
+    pub mark: Option<ProvenanceMark>,
+    /// The name of the policy rule that was violated, if any.
+    pub rule: Option<&'static str>,
+    /// Where the offending input was read from, if it came from a file.
+    pub source: Option<Source>,
+    /// The sequence numbers of the marks that are missing, for gaps.
+    pub missing: Option<RangeInclusive<u32>>,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(class: IssueClass, message: impl Into<String>) -> Self {
+        Self {
+            class,
+            chain_id: None,
+            mark: None,
+            rule: None,
+            source: None,
+            missing: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn in_chain(mut self, chain_id: impl Into<String>) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self
+    }
+
+    pub fn on_mark(mut self, mark: &ProvenanceMark) -> Self {
+        self.chain_id = Some(hex::encode(mark.chain_id()));
+        self.mark = Some(mark.clone());
+        self
+    }
+
+    pub fn with_rule(mut self, rule: &'static str) -> Self {
+        self.rule = Some(rule);
+        self
+    }
+
+    pub fn at_source(mut self, source: Option<Source>) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_missing(mut self, missing: RangeInclusive<u32>) -> Self {
+        self.missing = Some(missing);
+        self
+    }
+
+    pub fn seq(&self) -> Option<u32> {
+        self.mark.as_ref().map(|m| m.seq())
+    }
+
+    /// The class of the finding, qualified by its policy rule if any.
+    pub fn label(&self) -> String {
+        match self.rule {
+            Some(rule) => format!("{} ({})", self.class, rule),
+            None => self.class.to_string(),
+        }
+    }
+
+    /// A one-line description of the issue, including its location.
+    pub fn describe(&self) -> String {
+        match (&self.chain_id, self.seq()) {
+            (Some(chain_id), Some(seq)) => {
+                format!("chain {}, mark {}: {}", chain_id, seq, self.message)
+            }
+            (Some(chain_id), None) => {
+                format!("chain {}: {}", chain_id, self.message)
+            }
+            _ => match &self.source {
+                Some(source) => format!("{}: {}", source, self.message),
+                None => self.message.clone(),
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "class": self.class.name(),
+            "exitCode": self.class.exit_code(),
+            "rule": self.rule,
+            "chain": self.chain_id,
+            "seq": self.seq(),
+            "source": self.source.as_ref().map(|source| source.to_string()),
+            "message": self.message,
+        })
+    }
+}
+
+/// Classify every issue in a validation report.
+pub fn report_findings(report: &ValidationReport) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if report.chains().len() > 1 {
+        findings.push(Finding::new(
+            IssueClass::MultipleChains,
+            format!("marks belong to {} chains", report.chains().len()),
+        ));
+    }
+
+    for chain in report.chains() {
+        let chain_id = chain.chain_id_hex();
+        if !chain.has_genesis() {
+            findings.push(
+                Finding::new(
+                    IssueClass::MissingGenesis,
+                    "no genesis mark found",
+                )
+                .in_chain(&chain_id),
+            );
+        }
+        for sequence in chain.sequences() {
+            for flagged in sequence.marks() {
+                findings.extend(
+                    flagged.issues().iter().filter_map(|issue| {
+                        issue_finding(flagged.mark(), issue)
+                    }),
+                );
+            }
+        }
+        findings.extend(fork_findings(chain.marks()));
+    }
+
+    findings
+}
+
+/// Classify a single issue that the library found on a mark. Sequence numbers
+/// that repeat or go backwards yield nothing, as they are reported as forks.
+pub fn issue_finding(
+    mark: &ProvenanceMark,
+    issue: &ValidationIssue,
+) -> Option<Finding> {
+    let (class, message) = match issue {
+        ValidationIssue::SequenceGap { expected, actual } => {
+            // A sequence number that repeats or goes backwards is a fork,
+            // which is reported separately.
+            if actual <= expected {
+                return None;
+            }
+            let message = if *actual == expected + 1 {
+                format!("mark {} is missing", expected)
+            } else {
+                format!("marks {} through {} are missing", expected, actual - 1)
+            };
+            return Some(
+                Finding::new(IssueClass::Gap, message)
+                    .on_mark(mark)
+                    .with_missing(*expected..=actual - 1),
+            );
+        }
+        ValidationIssue::DateOrdering { previous, next } => (
+            IssueClass::DateOrder,
+            format!("dated {}, before its predecessor's {}", next, previous),
+        ),
+        ValidationIssue::HashMismatch { .. } => (
+            IssueClass::Mismatch,
+            "its key does not match its predecessor's hash".to_string(),
+        ),
+        ValidationIssue::KeyMismatch => (
+            IssueClass::Mismatch,
+            "its key does not link it to its predecessor".to_string(),
+        ),
+        ValidationIssue::NonGenesisAtZero => (
+            IssueClass::Mismatch,
+            "sequence 0 is not a valid genesis mark".to_string(),
+        ),
+        ValidationIssue::InvalidGenesisKey => (
+            IssueClass::Mismatch,
+            "a non-genesis mark uses the chain ID as its key".to_string(),
+        ),
+    };
+    Some(Finding::new(class, message).on_mark(mark))
+}
+
+/// Report every sequence number that is held by more than one distinct mark.
+fn fork_findings(marks: &[ProvenanceMark]) -> Vec<Finding> {
+    let mut marks = marks.to_vec();
+    marks.sort_by_key(|mark| mark.seq());
+    marks
+        .windows(2)
+        .filter(|pair| pair[0].seq() == pair[1].seq())
+        .map(|pair| {
+            Finding::new(
+                IssueClass::Fork,
+                format!(
+                    "conflicting marks {} and {} share this sequence number",
+                    pair[0].identifier(),
+                    pair[1].identifier()
+                ),
+            )
+            .on_mark(&pair[1])
+        })
+        .collect()
+}
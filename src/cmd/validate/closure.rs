@@ -0,0 +1,41 @@
+use provenance_mark::ValidationReport;
+
+use super::class::{Finding, IssueClass};
+use crate::cmd::close::ChainClosure;
+
+/// Find the marks that come after the mark that closed their chain.
+pub fn check(report: &ValidationReport) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for chain in report.chains() {
+        let closing = chain
+            .marks()
+            .iter()
+            .filter_map(|mark| Some((mark, ChainClosure::from_mark(mark)?)))
+            .min_by_key(|(mark, _)| mark.seq());
+        let Some((closing, closure)) = closing else {
+            continue;
+        };
+        findings.extend(
+            chain
+                .marks()
+                .iter()
+                .filter(|mark| mark.seq() > closing.seq())
+                .map(|mark| {
+                    Finding::new(
+                        IssueClass::AfterClose,
+                        message(closing.seq(), &closure),
+                    )
+                    .on_mark(mark)
+                }),
+        );
+    }
+    findings
+}
+
+/// Describe the closing mark that a later mark follows.
+pub fn message(closing_seq: u32, closure: &ChainClosure) -> String {
+    format!(
+        "the chain was closed at mark {} ({})",
+        closing_seq, closure.reason
+    )
+}
@@ -0,0 +1,26 @@
+use provenance_mark::{ProvenanceMark, ValidationReport};
+
+use super::class::{Finding, IssueClass};
+use crate::{cmd::notice::CompromiseNotice, trust_store::TrustStore};
+
+/// Find the marks that a compromise notice in the trust store distrusts,
+/// whether or not they link to their predecessors.
+pub fn check(report: &ValidationReport, trust: &TrustStore) -> Vec<Finding> {
+    report
+        .chains()
+        .iter()
+        .flat_map(|chain| chain.marks())
+        .filter_map(|mark| Some(finding(mark, trust.distrusting(mark)?)))
+        .collect()
+}
+
+pub fn finding(mark: &ProvenanceMark, notice: &CompromiseNotice) -> Finding {
+    let mut message = format!(
+        "a compromise notice distrusts {} of this chain",
+        notice.describe_cutoff()
+    );
+    if let Some(reason) = &notice.reason {
+        message.push_str(&format!(" ({})", reason));
+    }
+    Finding::new(IssueClass::Untrusted, message).on_mark(mark)
+}
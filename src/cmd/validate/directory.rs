@@ -0,0 +1,273 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow, bail};
+use provenance_mark::{ProvenanceMark, ProvenanceMarkInfo};
+
+use super::{
+    Inputs,
+    class::{Finding, IssueClass, Source},
+};
+use crate::{
+    chain_dir::{ChainDir, parse_generator},
+    config::CHAIN_CONFIG_FILE,
+};
+
+/// Load the marks of a chain directory, checking that the directory's files
+/// agree with the marks they hold.
+///
+/// Mark files are read in sequence order. Each is checked against its file
+/// name and against the `ur`, `bytewords`, and `bytemoji` fields stored beside
+/// the mark. If the directory has a `generator.json`, its chain ID and next
+/// sequence number are checked against the marks. JSON files that are not
+/// named like mark files are reported and skipped.
+pub fn load(path: &Path, inputs: &mut Inputs) -> Result<()> {
+    let (mark_files, stray) = mark_files(path)?;
+    inputs.findings.extend(stray);
+
+    // Parse each JSON file and extract the mark
+    let mut highest = Highest::default();
+    for mark_file in mark_files {
+        let (mark, findings) = read_mark_file(&mark_file)?;
+        inputs.findings.extend(findings);
+        if let Some(mark) = mark {
+            highest.update(&mark);
+            inputs.add_mark(mark, Some(source(&mark_file.path)));
+        }
+    }
+
+    inputs.findings.extend(check_generator(path, &highest));
+    Ok(())
+}
+
+/// List the mark files of a chain directory in sequence order, so that
+/// `mark-10.json` comes after `mark-2.json`, along with a finding for each
+/// stray JSON file.
+pub fn mark_files(path: &Path) -> Result<(Vec<MarkFile>, Vec<Finding>)> {
+    // Get the marks subdirectory
+    let marks_path = path.join("marks");
+    if !marks_path.exists() || !marks_path.is_dir() {
+        bail!("Marks subdirectory not found: {}", marks_path.display());
+    }
+
+    // Read all JSON files from the marks directory
+    let mut json_files: Vec<PathBuf> = fs::read_dir(&marks_path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension().and_then(|s| s.to_str()) == Some("json")
+        })
+        .collect();
+    json_files.sort();
+    if json_files.is_empty() {
+        bail!("No mark JSON files found in: {}", marks_path.display());
+    }
+
+    let mut mark_files = Vec::new();
+    let mut stray = Vec::new();
+    for file in json_files {
+        match file_seq(&file) {
+            Some(seq) => mark_files.push(MarkFile { seq, path: file }),
+            None => stray.push(
+                Finding::new(
+                    IssueClass::Directory,
+                    "stray file: not named like a mark file (mark-N.json)",
+                )
+                .at_source(Some(source(&file))),
+            ),
+        }
+    }
+    mark_files.sort_by_key(|file| file.seq);
+    Ok((mark_files, stray))
+}
+
+/// A file in a chain's `marks` directory, with the sequence number in its
+/// name.
+#[derive(Debug)]
+pub struct MarkFile {
+    pub seq: u32,
+    pub path: PathBuf,
+}
+
+/// Read one mark file, checking it against its name and the fields stored
+/// beside the mark. A file that cannot be parsed yields no mark and a `parse`
+/// finding.
+pub fn read_mark_file(
+    mark_file: &MarkFile,
+) -> Result<(Option<ProvenanceMark>, Vec<Finding>)> {
+    let MarkFile {
+        seq: file_seq,
+        path: mark_file,
+    } = mark_file;
+    let json_content = fs::read_to_string(mark_file).map_err(|e| {
+        anyhow!("Failed to read {}: {}", mark_file.display(), e)
+    })?;
+    let mark_info =
+        match serde_json::from_str::<ProvenanceMarkInfo>(&json_content) {
+            Ok(mark_info) => mark_info,
+            Err(e) => {
+                let finding = Finding::new(
+                    IssueClass::Parse,
+                    format!(
+                        "Failed to parse JSON from {}: {}",
+                        mark_file.display(),
+                        e
+                    ),
+                )
+                .at_source(Some(source(mark_file)));
+                return Ok((None, vec![finding]));
+            }
+        };
+    let mark = mark_info.mark().clone();
+
+    let mut problems = Vec::new();
+    if mark.seq() != *file_seq {
+        problems.push(format!(
+            "file is named for mark {} but holds mark {}",
+            file_seq,
+            mark.seq()
+        ));
+    }
+    problems.extend(stored_field_problems(&json_content, &mark_info));
+    let findings = problems
+        .into_iter()
+        .map(|problem| {
+            Finding::new(IssueClass::Directory, problem)
+                .on_mark(&mark)
+                .at_source(Some(source(mark_file)))
+        })
+        .collect();
+    Ok((Some(mark), findings))
+}
+
+/// The highest mark read from a chain directory, which the generator should
+/// follow.
+#[derive(Debug, Default)]
+pub struct Highest(Option<(u32, Vec<u8>)>);
+
+impl Highest {
+    pub fn update(&mut self, mark: &ProvenanceMark) {
+        if self.0.as_ref().is_none_or(|(seq, _)| mark.seq() > *seq) {
+            self.0 = Some((mark.seq(), mark.chain_id().to_vec()));
+        }
+    }
+}
+
+/// Check the chain's generator, if it has one that can be read, against the
+/// highest mark in the directory. The generator is found as `chain.toml`
+/// says, or in `generator.json`.
+pub fn check_generator(path: &Path, highest: &Highest) -> Vec<Finding> {
+    let chain_dir = match ChainDir::open(&path.to_path_buf()) {
+        Ok(chain_dir) => chain_dir,
+        Err(e) => {
+            return vec![
+                Finding::new(IssueClass::Directory, e.to_string())
+                    .at_source(Some(source(&path.join(CHAIN_CONFIG_FILE)))),
+            ];
+        }
+    };
+    let generator_path = chain_dir.generator_path();
+    if generator_path.exists() {
+        generator_problems(&generator_path, highest.0.as_ref())
+    } else {
+        Vec::new()
+    }
+}
+
+/// Load the marks of a chain directory, reporting a directory that cannot be
+/// read at all as an issue rather than an error.
+pub fn load_all(path: &Path) -> Inputs {
+    let mut inputs = Inputs::default();
+    if let Err(e) = load(path, &mut inputs) {
+        inputs.findings.push(
+            Finding::new(IssueClass::Directory, e.to_string())
+                .at_source(Some(source(path))),
+        );
+    }
+    inputs
+}
+
+pub fn source(path: &Path) -> Source {
+    Source {
+        path: path.to_path_buf(),
+        line: None,
+    }
+}
+
+/// The sequence number in a file name of the form `mark-N.json`.
+fn file_seq(path: &Path) -> Option<u32> {
+    let name = path.file_name()?.to_str()?;
+    let digits = name.strip_prefix("mark-")?.strip_suffix(".json")?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Compare the `ur`, `bytewords`, and `bytemoji` fields as written in the file
+/// with those derived from the mark it holds.
+fn stored_field_problems(
+    json_content: &str,
+    mark_info: &ProvenanceMarkInfo,
+) -> Vec<String> {
+    let Ok(stored) = serde_json::from_str::<serde_json::Value>(json_content)
+    else {
+        return Vec::new();
+    };
+    let expected =
+        ProvenanceMarkInfo::new(mark_info.mark().clone(), mark_info.comment());
+    let fields = [
+        ("ur", expected.ur().to_string()),
+        ("bytewords", expected.bytewords().to_string()),
+        ("bytemoji", expected.bytemoji().to_string()),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(field, expected)| match stored[field].as_str() {
+            Some(value) if value == expected => None,
+            Some(_) => {
+                Some(format!("stored `{}` does not match the mark", field))
+            }
+            None => Some(format!("stored `{}` is missing", field)),
+        })
+        .collect()
+}
+
+/// Check the chain's generator against the highest mark in the directory.
+fn generator_problems(
+    generator_path: &Path,
+    highest: Option<&(u32, Vec<u8>)>,
+) -> Vec<Finding> {
+    let finding = |message: String| {
+        Finding::new(IssueClass::Directory, message)
+            .at_source(Some(source(generator_path)))
+    };
+    let generator = match fs::read_to_string(generator_path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| parse_generator(&json).map_err(|e| e.to_string()))
+    {
+        Ok((generator, _)) => generator,
+        Err(e) => return vec![finding(format!("unreadable generator: {}", e))],
+    };
+    let Some((highest_seq, chain_id)) = highest else {
+        return Vec::new();
+    };
+
+    let mut findings = Vec::new();
+    if generator.chain_id() != chain_id.as_slice() {
+        findings.push(finding(format!(
+            "generator is for chain {}, not the marks' chain",
+            hex::encode(generator.chain_id())
+        )));
+    }
+    if generator.next_seq() != highest_seq + 1 {
+        findings.push(finding(format!(
+            "generator's next sequence number is {}, but the highest \
+                 mark is {}",
+            generator.next_seq(),
+            highest_seq
+        )));
+    }
+    findings
+}
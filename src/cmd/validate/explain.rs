@@ -0,0 +1,249 @@
+use std::{ops::RangeInclusive, path::Path};
+
+use super::class::{Finding, IssueClass};
+
+/// Explain a finding for readers who do not know the validator's terms: what
+/// it means, how serious it is, and what to do about it.
+pub fn explain(finding: &Finding, fatal: bool, dir: Option<&Path>) -> String {
+    let chain_dir = dir
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| "<chain-dir>".to_string());
+    let print = |range: &RangeInclusive<u32>| {
+        format!(
+            "`provenance print {} --start {} --end {}`",
+            chain_dir,
+            range.start(),
+            range.end()
+        )
+    };
+
+    let (meaning, severity, remedy) = match finding.class {
+        IssueClass::Parse => (
+            format!(
+                "An input could not be read as a provenance mark ({}), so it \
+                 was left out of validation.",
+                finding.message
+            ),
+            "Moderate: the input may be corrupted or mistyped, and any \
+             problem it would reveal stays hidden.",
+            "Check that the input is a complete `ur:provenance` UR, or an \
+             envelope that carries one, and that it was not truncated when \
+             copied."
+                .to_string(),
+        ),
+        IssueClass::MultipleChains => (
+            format!(
+                "The marks do not all belong to the same chain: {}.",
+                finding.message
+            ),
+            "Low if you meant to check several chains at once; high if you \
+             expected one, because a mark from another chain says nothing \
+             about this one.",
+            "Validate each chain on its own, or remove the marks that do not \
+             belong."
+                .to_string(),
+        ),
+        IssueClass::Gap => {
+            let missing = finding.missing.clone();
+            (
+                match &missing {
+                    Some(range) if range.start() == range.end() => {
+                        format!("Mark {} is missing.", range.start())
+                    }
+                    Some(range) => format!(
+                        "Marks {} through {} are missing.",
+                        range.start(),
+                        range.end()
+                    ),
+                    None => format!("Marks are missing: {}.", finding.message),
+                },
+                "Low: the marks that are present still link together, but \
+                 nothing can be said about the ones that are missing.",
+                match &missing {
+                    Some(range) => format!(
+                        "Ask the publisher for the missing marks, or run {} \
+                         on the source chain and validate them together with \
+                         these.",
+                        print(range)
+                    ),
+                    None => {
+                        "Ask the publisher for the missing marks.".to_string()
+                    }
+                },
+            )
+        }
+        IssueClass::MissingGenesis => (
+            "The chain's first mark (sequence 0) is not among the marks, so \
+             the chain cannot be traced back to its start."
+                .to_string(),
+            "Moderate: the marks may be genuine, but they cannot be tied to \
+             the chain's origin.",
+            format!(
+                "Ask the publisher for the genesis mark, or run {} on the \
+                 source chain. If you only hold an excerpt on purpose, use \
+                 `--allow missing-genesis`.",
+                print(&(0..=0))
+            ),
+        ),
+        IssueClass::DateOrder => (
+            format!(
+                "The mark is {}. A chain's dates must never go backwards.",
+                finding.message
+            ),
+            "High: this points to a wrong clock on the machine that made the \
+             mark, or to tampering.",
+            "Check the clock of the machine that generated the mark. If the \
+             chain is yours, find the cause before publishing more marks."
+                .to_string(),
+        ),
+        IssueClass::Mismatch => (
+            format!(
+                "The mark does not link cryptographically to the chain: {}.",
+                finding.message
+            ),
+            "Critical: the mark may be forged, altered, or made from a \
+             different copy of the generator.",
+            "Do not trust the mark. Obtain it again from the publisher and \
+             compare it with this copy."
+                .to_string(),
+        ),
+        IssueClass::Fork => (
+            format!(
+                "Two different marks claim the same place in the chain: {}.",
+                finding.message
+            ),
+            "Critical: the generator was probably copied or rolled back, or \
+             one of the marks is forged.",
+            "Find out which mark was really published. If the chain is yours, \
+             stop generating marks until the generator is restored from a \
+             trusted copy."
+                .to_string(),
+        ),
+        IssueClass::Policy => (
+            format!(
+                "The chain breaks the `{}` rule of the validation policy: {}.",
+                finding.rule.unwrap_or("unknown"),
+                finding.message
+            ),
+            "As serious as your policy makes it.",
+            "Bring the chain into line with the policy, or change the policy \
+             if the rule no longer applies."
+                .to_string(),
+        ),
+        IssueClass::Stale => (
+            format!(
+                "The chain has not published a mark recently: its {}.",
+                finding.message
+            ),
+            "Moderate: the chain may have been abandoned, or you may not \
+             have its latest marks.",
+            "Fetch the chain's latest marks from the publisher. If there are \
+             none, ask whether the chain is still in use."
+                .to_string(),
+        ),
+        IssueClass::Silence => (
+            format!(
+                "The chain went quiet for longer than expected: {}.",
+                finding.message
+            ),
+            "Low: the marks still link together, but the chain was not \
+             vouching for anything during the silence.",
+            "Ask the publisher why no marks were made during that time. If \
+             the chain is yours, generate marks on a regular schedule."
+                .to_string(),
+        ),
+        IssueClass::Directory => (
+            format!(
+                "The chain directory's files disagree with the marks they \
+                 hold: {}.",
+                finding.message
+            ),
+            "Moderate: the marks themselves may be sound, but the directory \
+             has been edited by hand, damaged, or mixed with files from \
+             elsewhere.",
+            "Restore the directory from a backup, or move the offending file \
+             aside. Never edit mark files or `generator.json` by hand."
+                .to_string(),
+        ),
+        IssueClass::AfterClose => (
+            format!(
+                "The chain was closed with a final mark, yet another mark \
+                 follows it: {}.",
+                finding.message
+            ),
+            "High: once a chain is closed its owner issues no more marks, so \
+             a later mark was most likely made by someone holding a stolen \
+             or leaked seed.",
+            "Do not trust the later mark. Ask the chain's owner why the chain \
+             was closed and which chain replaces it."
+                .to_string(),
+        ),
+        IssueClass::Succession => (
+            format!(
+                "A chain claims to succeed another, but the two chains do not \
+                 agree: {}.",
+                finding.message
+            ),
+            "High: a genuine successor names its predecessor's head, and the \
+             predecessor is closed by a mark that names the successor. \
+             Without both halves, either chain may be an impostor.",
+            "Check that you have every mark of both chains. If you do, treat \
+             the newer chain as unrelated to the older one until its owner \
+             explains the broken link."
+                .to_string(),
+        ),
+        IssueClass::Untrusted => (
+            format!(
+                "The chain's owner has published a notice that its generator \
+                 was compromised, and this mark comes after the last point \
+                 they still trust: {}.",
+                finding.message
+            ),
+            "High: the mark may link correctly to its predecessor, but anyone \
+             holding the leaked generator could have made it.",
+            "Do not rely on the mark. Look for the chain that replaces this \
+             one, and ask the owner to reissue anything that the mark was \
+             meant to vouch for."
+                .to_string(),
+        ),
+    };
+
+    let outcome = if fatal {
+        format!(
+            "This issue fails validation (exit code {}).",
+            finding.class.exit_code()
+        )
+    } else {
+        "This issue is allowed and does not fail validation.".to_string()
+    };
+
+    format!(
+        "{}\n  {} Severity: {} To fix: {} {}",
+        heading(finding),
+        meaning,
+        severity,
+        remedy,
+        outcome
+    )
+}
+
+/// The finding's class and location, with the mark's identifier in the
+/// bytewords and bytemoji forms that people see when marks are published.
+fn heading(finding: &Finding) -> String {
+    let mut heading = finding.label();
+    if let Some(chain_id) = &finding.chain_id {
+        heading.push_str(&format!(", chain {}", chain_id));
+    }
+    if let Some(mark) = &finding.mark {
+        heading.push_str(&format!(
+            ", mark {} ({} / {})",
+            mark.seq(),
+            mark.bytewords_identifier(true),
+            mark.bytemoji_identifier(true)
+        ));
+    }
+    if let Some(source) = &finding.source {
+        heading.push_str(&format!(", {}", source));
+    }
+    heading
+}
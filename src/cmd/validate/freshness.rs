@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use dcbor::prelude::*;
+use provenance_mark::{ProvenanceMark, ValidationReport};
+
+use super::class::{Finding, IssueClass};
+use crate::utils::format_duration;
+
+/// Checks that each chain is still alive: that its newest mark is recent
+/// enough, and that it has never gone silent for too long.
+#[derive(Debug)]
+pub struct Freshness {
+    /// The oldest acceptable age of a chain's newest mark.
+    pub max_age: Option<Duration>,
+    /// The longest acceptable time between consecutive marks.
+    pub max_interval: Option<Duration>,
+    /// The time that ages are measured against.
+    pub now: Date,
+}
+
+impl Freshness {
+    pub fn check(&self, report: &ValidationReport) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for chain in report.chains() {
+            let mut marks = chain.marks().to_vec();
+            marks.sort_by_key(|mark| mark.seq());
+
+            if let Some(newest) = marks.iter().max_by_key(|mark| mark.date()) {
+                findings.extend(self.check_newest(newest));
+            }
+
+            if let Some(max) = self.max_interval {
+                findings.extend(long_intervals(&marks, max).map(
+                    |(_, next, message)| {
+                        Finding::new(IssueClass::Silence, message).on_mark(next)
+                    },
+                ));
+            }
+        }
+        findings
+    }
+
+    /// Check the age of a chain's newest mark against `max_age`.
+    pub fn check_newest(&self, newest: &ProvenanceMark) -> Option<Finding> {
+        let max = self.max_age?;
+        let age = self.now.timestamp() - newest.date().timestamp();
+        (age > max.as_secs_f64()).then(|| {
+            Finding::new(
+                IssueClass::Stale,
+                format!(
+                    "newest mark is {} old, older than {}",
+                    format_duration(age),
+                    format_duration(max.as_secs_f64())
+                ),
+            )
+            .on_mark(newest)
+        })
+    }
+}
+
+/// Find each pair of consecutive marks, sorted by sequence number, that are
+/// further apart in time than `max`, with a message describing the interval.
+pub fn long_intervals(
+    marks: &[ProvenanceMark],
+    max: Duration,
+) -> impl Iterator<Item = (&ProvenanceMark, &ProvenanceMark, String)> {
+    marks.windows(2).filter_map(move |pair| {
+        let interval = pair[1].date().timestamp() - pair[0].date().timestamp();
+        (interval > max.as_secs_f64()).then(|| {
+            let message = format!(
+                "{} since mark {} exceeds {}",
+                format_duration(interval),
+                pair[0].seq(),
+                format_duration(max.as_secs_f64())
+            );
+            (&pair[0], &pair[1], message)
+        })
+    })
+}
@@ -0,0 +1,221 @@
+use std::fmt::Write;
+
+use provenance_mark::ProvenanceMark;
+
+use super::{
+    Checked,
+    class::{Finding, IssueClass, Source},
+};
+
+/// Render the validation results as JUnit XML. Each chain becomes a test
+/// suite with one test case per mark and one for the chain as a whole;
+/// issues that belong to no chain go in a separate "inputs" suite. When
+/// several chain directories were validated, suite names are prefixed with
+/// the directory.
+pub fn render(
+    checked: &[Checked],
+    is_fatal: &dyn Fn(IssueClass) -> bool,
+) -> String {
+    let mut suites = Vec::new();
+    for checked in checked {
+        suites.extend(suites_for(checked));
+    }
+
+    let tests: usize = suites.iter().map(|suite| suite.cases.len()).sum();
+    let failures: usize =
+        suites.iter().map(|suite| suite.failures(is_fatal)).sum();
+
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        xml,
+        r#"<testsuites name="provenance validate" tests="{}" failures="{}">"#,
+        tests, failures
+    )
+    .unwrap();
+    for suite in &suites {
+        suite.write(&mut xml, is_fatal);
+    }
+    write!(xml, "</testsuites>").unwrap();
+    xml
+}
+
+fn suites_for(checked: &Checked) -> Vec<TestSuite<'_>> {
+    let prefix = checked
+        .dir
+        .as_ref()
+        .map(|dir| format!("{}: ", dir.display()))
+        .unwrap_or_default();
+    let mut suites = Vec::new();
+
+    for chain in checked.report.chains() {
+        let chain_id = chain.chain_id_hex();
+        let in_chain: Vec<&Finding> = checked
+            .findings
+            .iter()
+            .filter(|finding| finding.chain_id.as_deref() == Some(&chain_id))
+            .collect();
+
+        let mut cases = vec![TestCase {
+            name: "chain".to_string(),
+            source: None,
+            findings: in_chain
+                .iter()
+                .copied()
+                .filter(|finding| finding.mark.is_none())
+                .collect(),
+        }];
+        let mut marks: Vec<&ProvenanceMark> = chain.marks().iter().collect();
+        marks.sort_by_key(|mark| mark.seq());
+        for mark in marks {
+            cases.push(TestCase {
+                name: format!("mark {}", mark.seq()),
+                source: checked.sources.get(mark.hash()),
+                findings: in_chain
+                    .iter()
+                    .copied()
+                    .filter(|finding| finding.mark.as_ref() == Some(mark))
+                    .collect(),
+            });
+        }
+        suites.push(TestSuite {
+            name: format!("{}chain {}", prefix, chain_id),
+            cases,
+        });
+    }
+
+    let unchained: Vec<&Finding> = checked
+        .findings
+        .iter()
+        .filter(|finding| finding.chain_id.is_none())
+        .collect();
+    if !unchained.is_empty() {
+        suites.push(TestSuite {
+            name: format!("{}inputs", prefix),
+            cases: unchained
+                .into_iter()
+                .map(|finding| TestCase {
+                    name: finding.class.to_string(),
+                    source: finding.source.as_ref(),
+                    findings: vec![finding],
+                })
+                .collect(),
+        });
+    }
+
+    suites
+}
+
+struct TestSuite<'a> {
+    name: String,
+    cases: Vec<TestCase<'a>>,
+}
+
+impl TestSuite<'_> {
+    fn failures(&self, is_fatal: &dyn Fn(IssueClass) -> bool) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.is_failure(is_fatal))
+            .count()
+    }
+
+    fn write(&self, xml: &mut String, is_fatal: &dyn Fn(IssueClass) -> bool) {
+        writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}" failures="{}">"#,
+            xml_escape(&self.name),
+            self.cases.len(),
+            self.failures(is_fatal)
+        )
+        .unwrap();
+        for case in &self.cases {
+            case.write(xml, &self.name, is_fatal);
+        }
+        writeln!(xml, "  </testsuite>").unwrap();
+    }
+}
+
+struct TestCase<'a> {
+    name: String,
+    source: Option<&'a Source>,
+    findings: Vec<&'a Finding>,
+}
+
+impl TestCase<'_> {
+    fn is_failure(&self, is_fatal: &dyn Fn(IssueClass) -> bool) -> bool {
+        self.findings.iter().any(|finding| is_fatal(finding.class))
+    }
+
+    fn write(
+        &self,
+        xml: &mut String,
+        classname: &str,
+        is_fatal: &dyn Fn(IssueClass) -> bool,
+    ) {
+        let mut attributes = format!(
+            r#"name="{}" classname="{}""#,
+            xml_escape(&self.name),
+            xml_escape(classname)
+        );
+        if let Some(source) = self.source {
+            write!(
+                attributes,
+                r#" file="{}""#,
+                xml_escape(&source.path.display().to_string())
+            )
+            .unwrap();
+            if let Some(line) = source.line {
+                write!(attributes, r#" line="{}""#, line).unwrap();
+            }
+        }
+        if self.findings.is_empty() {
+            writeln!(xml, "    <testcase {}/>", attributes).unwrap();
+            return;
+        }
+
+        writeln!(xml, "    <testcase {}>", attributes).unwrap();
+        let (fatal, allowed): (Vec<&Finding>, Vec<&Finding>) = self
+            .findings
+            .iter()
+            .partition(|finding| is_fatal(finding.class));
+        for finding in fatal {
+            writeln!(
+                xml,
+                r#"      <failure type="{}" message="{}"/>"#,
+                xml_escape(&finding.label()),
+                xml_escape(&finding.describe())
+            )
+            .unwrap();
+        }
+        if !allowed.is_empty() {
+            let lines: Vec<String> = allowed
+                .iter()
+                .map(|finding| {
+                    format!("{}: {}", finding.label(), finding.describe())
+                })
+                .collect();
+            writeln!(
+                xml,
+                "      <system-out>{}</system-out>",
+                xml_escape(&lines.join("\n"))
+            )
+            .unwrap();
+        }
+        writeln!(xml, "    </testcase>").unwrap();
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
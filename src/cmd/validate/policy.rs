@@ -0,0 +1,157 @@
+use std::{fs, path::Path, time::Duration};
+
+use anyhow::{Result, anyhow, bail};
+use bc_components::Digest;
+use clap::ValueEnum;
+use provenance_mark::{ProvenanceMark, ValidationReport};
+use serde::Deserialize;
+
+use super::{
+    class::{Finding, IssueClass},
+    freshness::long_intervals,
+};
+use crate::{cmd::new::Resolution, utils::parse_duration};
+
+/// The policy file as written by the user. Every rule is optional.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct PolicyFile {
+    require_genesis: Option<bool>,
+    max_interval: Option<String>,
+    min_resolution: Option<String>,
+    require_info_digest: Option<bool>,
+    chain_id: Option<String>,
+}
+
+/// A declarative set of rules checked on top of a validation report.
+#[derive(Debug)]
+pub struct Policy {
+    /// `requireGenesis`: every chain must include its genesis mark.
+    require_genesis: bool,
+    /// `maxInterval`: the longest allowed time between consecutive marks.
+    max_interval: Option<Duration>,
+    /// `minResolution`: the lowest acceptable chain resolution.
+    min_resolution: Option<Resolution>,
+    /// `requireInfoDigest`: every mark's `info` must hold a digest.
+    require_info_digest: bool,
+    /// `chainId`: the chain every mark must belong to.
+    chain_id: Option<String>,
+}
+
+impl Policy {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path).map_err(|e| {
+            anyhow!("Failed to read policy {}: {}", path.display(), e)
+        })?;
+        let file: PolicyFile = serde_json::from_str(&json).map_err(|e| {
+            anyhow!("Failed to parse policy {}: {}", path.display(), e)
+        })?;
+
+        let max_interval = file
+            .max_interval
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+            .map_err(|e| anyhow!("Invalid maxInterval in policy: {}", e))?;
+        let min_resolution = file
+            .min_resolution
+            .as_deref()
+            .map(|name| Resolution::from_str(name, true))
+            .transpose()
+            .map_err(|e| anyhow!("Invalid minResolution in policy: {}", e))?;
+        let chain_id = file.chain_id.map(|id| id.to_lowercase());
+        if let Some(chain_id) = &chain_id
+            && hex::decode(chain_id).is_err()
+        {
+            bail!("Invalid chainId in policy: expected hex, got {}", chain_id);
+        }
+
+        Ok(Self {
+            require_genesis: file.require_genesis.unwrap_or(false),
+            max_interval,
+            min_resolution,
+            require_info_digest: file.require_info_digest.unwrap_or(false),
+            chain_id,
+        })
+    }
+
+    /// Check every rule against the report, returning one finding per
+    /// violation.
+    pub fn check(&self, report: &ValidationReport) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for chain in report.chains() {
+            let chain_id = chain.chain_id_hex();
+            let mut marks = chain.marks().to_vec();
+            marks.sort_by_key(|mark| mark.seq());
+
+            if let Some(expected) = &self.chain_id
+                && &chain_id != expected
+            {
+                findings.push(
+                    violation(
+                        "chainId",
+                        format!("chain is not the required chain {}", expected),
+                    )
+                    .in_chain(&chain_id),
+                );
+            }
+
+            if self.require_genesis && !chain.has_genesis() {
+                findings.push(
+                    violation("requireGenesis", "chain has no genesis mark")
+                        .in_chain(&chain_id),
+                );
+            }
+
+            if let (Some(min), Some(first)) =
+                (self.min_resolution, marks.first())
+            {
+                let resolution =
+                    Resolution::from_provenance_mark_resolution(first.res());
+                if resolution < min {
+                    findings.push(
+                        violation(
+                            "minResolution",
+                            format!(
+                                "resolution {} is below the required {}",
+                                resolution, min
+                            ),
+                        )
+                        .in_chain(&chain_id),
+                    );
+                }
+            }
+
+            if let Some(max) = self.max_interval {
+                findings.extend(long_intervals(&marks, max).map(
+                    |(_, next, message)| {
+                        violation("maxInterval", message).on_mark(next)
+                    },
+                ));
+            }
+
+            if self.require_info_digest {
+                findings.extend(marks.iter().filter_map(|mark| {
+                    info_digest_violation(mark).map(|message| {
+                        violation("requireInfoDigest", message).on_mark(mark)
+                    })
+                }));
+            }
+        }
+        findings
+    }
+}
+
+fn violation(rule: &'static str, message: impl Into<String>) -> Finding {
+    Finding::new(IssueClass::Policy, message).with_rule(rule)
+}
+
+fn info_digest_violation(mark: &ProvenanceMark) -> Option<&'static str> {
+    match mark.info() {
+        None => Some("mark has no info"),
+        Some(info) => match Digest::try_from(info) {
+            Ok(_) => None,
+            Err(_) => Some("mark info is not a digest"),
+        },
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use anyhow::{Result, bail};
+use serde_json::json;
+
+use super::{
+    Checked, CommandArgs, Format,
+    class::{Finding, IssueClass},
+};
+
+/// Find every chain directory under `root`: each directory that holds a
+/// `generator.json` or a `marks` subdirectory. Chain directories are not
+/// searched further, and hidden directories such as `.git` are skipped.
+pub fn find_chain_dirs(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    search(root, &mut dirs)?;
+    if dirs.is_empty() {
+        bail!("No chain directories found under: {}", root.display());
+    }
+    Ok(dirs)
+}
+
+fn search(dir: &Path, dirs: &mut Vec<PathBuf>) -> Result<()> {
+    if dir.join("generator.json").is_file() || dir.join("marks").is_dir() {
+        dirs.push(dir.to_path_buf());
+        return Ok(());
+    }
+
+    let mut subdirs: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path())
+        .collect();
+    subdirs.sort();
+    for subdir in subdirs {
+        search(&subdir, dirs)?;
+    }
+    Ok(())
+}
+
+/// Check each directory on a pool of threads, returning the results in the
+/// same order as `dirs`.
+pub fn check_all<F>(dirs: &[PathBuf], check: F) -> Vec<Checked>
+where
+    F: Fn(&Path) -> Checked + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(dirs.len());
+
+    let mut results: Vec<(usize, Checked)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(dir) = dirs.get(index) else {
+                            break;
+                        };
+                        results.push((index, check(dir)));
+                    }
+                    results
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle.join().expect("validation thread panicked")
+            })
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, checked)| checked).collect()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn of(args: &CommandArgs, checked: &Checked) -> Self {
+        if checked.findings.iter().any(|f| args.is_fatal(f.class)) {
+            Status::Fail
+        } else if !checked.findings.is_empty() {
+            Status::Warn
+        } else {
+            Status::Ok
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+        }
+    }
+}
+
+/// The directory's path relative to the root, for display.
+fn relative_path(root: &Path, checked: &Checked) -> String {
+    let dir = checked.dir.as_deref().unwrap_or(root);
+    match dir.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => dir.display().to_string(),
+    }
+}
+
+struct Totals {
+    marks: usize,
+    passed: usize,
+    warned: usize,
+    failed: usize,
+}
+
+fn totals(args: &CommandArgs, checked: &[Checked]) -> Totals {
+    let mut totals = Totals {
+        marks: 0,
+        passed: 0,
+        warned: 0,
+        failed: 0,
+    };
+    for checked in checked {
+        totals.marks += checked.report.marks().len();
+        match Status::of(args, checked) {
+            Status::Ok => totals.passed += 1,
+            Status::Warn => totals.warned += 1,
+            Status::Fail => totals.failed += 1,
+        }
+    }
+    totals
+}
+
+/// Render one line per chain directory with its status, followed by its
+/// issues, and a summary line with the totals.
+pub fn render_text(
+    args: &CommandArgs,
+    root: &Path,
+    checked: &[Checked],
+) -> String {
+    let mut lines = Vec::new();
+    for checked in checked {
+        lines.push(format!(
+            "{:<4} {} ({} marks)",
+            Status::of(args, checked).name(),
+            relative_path(root, checked),
+            checked.report.marks().len()
+        ));
+        for finding in &checked.findings {
+            lines.push(format!(
+                "  {}: {}",
+                finding.label(),
+                finding.describe()
+            ));
+            if args.explain {
+                let explanation = args.explain(finding, checked.dir.as_deref());
+                lines.extend(
+                    explanation
+                        .lines()
+                        .skip(1)
+                        .map(|line| format!("  {}", line)),
+                );
+            }
+        }
+    }
+
+    let totals = totals(args, checked);
+    lines.push(String::new());
+    lines.push(format!(
+        "{} chain directories, {} marks: {} passed, {} with warnings, {} failed",
+        checked.len(),
+        totals.marks,
+        totals.passed,
+        totals.warned,
+        totals.failed
+    ));
+    lines.join("\n")
+}
+
+/// Render the status and issues of each chain directory, with totals, as JSON.
+pub fn render_json(
+    args: &CommandArgs,
+    root: &Path,
+    checked: &[Checked],
+) -> Result<String> {
+    let directories: Vec<serde_json::Value> = checked
+        .iter()
+        .map(|checked| {
+            let status = Status::of(args, checked);
+            let exit_code = IssueClass::most_severe(
+                checked
+                    .findings
+                    .iter()
+                    .map(|finding| finding.class)
+                    .filter(|class| args.is_fatal(*class)),
+            )
+            .map(|class| class.exit_code());
+            let chains: Vec<String> = checked
+                .report
+                .chains()
+                .iter()
+                .map(|chain| chain.chain_id_hex())
+                .collect();
+            let issues: Vec<serde_json::Value> = checked
+                .findings
+                .iter()
+                .map(|finding| issue_json(args, checked, finding))
+                .collect();
+            json!({
+                "path": relative_path(root, checked),
+                "status": status.name(),
+                "exitCode": exit_code,
+                "marks": checked.report.marks().len(),
+                "chains": chains,
+                "issues": issues,
+            })
+        })
+        .collect();
+
+    let totals = totals(args, checked);
+    let json = json!({
+        "root": root.display().to_string(),
+        "directories": directories,
+        "totals": {
+            "directories": checked.len(),
+            "marks": totals.marks,
+            "passed": totals.passed,
+            "warned": totals.warned,
+            "failed": totals.failed,
+        },
+    });
+    if args.format == Format::JsonPretty {
+        Ok(serde_json::to_string_pretty(&json)?)
+    } else {
+        Ok(serde_json::to_string(&json)?)
+    }
+}
+
+fn issue_json(
+    args: &CommandArgs,
+    checked: &Checked,
+    finding: &Finding,
+) -> serde_json::Value {
+    let mut issue = finding.to_json();
+    if args.explain {
+        issue["explanation"] =
+            args.explain(finding, checked.dir.as_deref()).into();
+    }
+    issue
+}
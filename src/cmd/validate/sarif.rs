@@ -0,0 +1,85 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+use super::class::{Finding, IssueClass};
+
+/// Render the findings as a SARIF 2.1.0 log, with one rule per issue class
+/// and one result per finding.
+pub fn render<'a>(
+    findings: impl IntoIterator<Item = &'a Finding>,
+    is_fatal: &dyn Fn(IssueClass) -> bool,
+) -> Result<String> {
+    let rules: Vec<Value> = IssueClass::value_variants()
+        .iter()
+        .map(|class| {
+            json!({
+                "id": class.name(),
+                "shortDescription": { "text": class.description() },
+                "properties": { "exitCode": class.exit_code() },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = findings
+        .into_iter()
+        .map(|finding| result(finding, is_fatal(finding.class)))
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "provenance",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    });
+    Ok(serde_json::to_string_pretty(&log)?)
+}
+
+fn result(finding: &Finding, fatal: bool) -> Value {
+    let mut location = serde_json::Map::new();
+    if let Some(source) = &finding.source {
+        let mut physical = json!({
+            "artifactLocation": {
+                "uri": source.path.to_string_lossy().replace('\\', "/"),
+            }
+        });
+        if let Some(line) = source.line {
+            physical["region"] = json!({ "startLine": line });
+        }
+        location.insert("physicalLocation".to_string(), physical);
+    }
+    if let Some(chain_id) = &finding.chain_id {
+        let name = match finding.seq() {
+            Some(seq) => format!("chain {}/mark {}", chain_id, seq),
+            None => format!("chain {}", chain_id),
+        };
+        location.insert(
+            "logicalLocations".to_string(),
+            json!([{ "fullyQualifiedName": name }]),
+        );
+    }
+
+    let mut result = json!({
+        "ruleId": finding.class.name(),
+        "level": if fatal { "error" } else { "warning" },
+        "message": { "text": finding.describe() },
+        "properties": {
+            "chain": finding.chain_id,
+            "seq": finding.seq(),
+            "rule": finding.rule,
+        },
+    });
+    if !location.is_empty() {
+        result["locations"] = json!([location]);
+    }
+    result
+}
@@ -0,0 +1,416 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    thread,
+};
+
+use anyhow::{Result, anyhow, bail};
+use provenance_mark::{ProvenanceMark, ValidationIssue};
+
+use super::{
+    CommandArgs, Format,
+    class::{self, Finding, IssueClass, Source},
+    closure, compromise,
+    directory::{self, Highest, MarkFile},
+    freshness::{Freshness, long_intervals},
+    ur_tokens,
+};
+use crate::{
+    cmd::{close::ChainClosure, extract},
+    trust_store::TrustStore,
+    utils::read_existing_directory_path,
+};
+
+/// The number of inputs decoded in parallel before they are checked.
+const BATCH_SIZE: usize = 4096;
+
+/// An input to be decoded into a mark.
+enum Input {
+    Ur {
+        text: String,
+        source: Option<Source>,
+    },
+    File(MarkFile),
+}
+
+/// A decoded input: the mark, if there was one, and any issues found while
+/// decoding it.
+struct Decoded {
+    mark: Option<ProvenanceMark>,
+    source: Option<Source>,
+    findings: Vec<Finding>,
+}
+
+/// Validate the marks as they are decoded, keeping only the latest mark of
+/// each chain, and write each issue as soon as it is found. Returns the most
+/// severe fatal class of issue, if any.
+///
+/// Each chain's marks must arrive in sequence order, as they do from a chain
+/// directory.
+pub fn run(
+    args: &CommandArgs,
+    freshness: &Freshness,
+    trust: &TrustStore,
+    out: &mut dyn Write,
+) -> Result<Option<IssueClass>> {
+    if !matches!(args.format, Format::Text | Format::JsonCompact) {
+        bail!("--stream supports only --format text or json-compact");
+    }
+
+    let mut checker = Checker {
+        args,
+        freshness,
+        trust,
+        out,
+        chains: HashMap::new(),
+        chain_order: Vec::new(),
+        marks: 0,
+        issues: 0,
+        fatal: None,
+    };
+
+    let dir = args
+        .dir
+        .as_ref()
+        .map(read_existing_directory_path)
+        .transpose()?;
+    let mut inputs: Box<dyn Iterator<Item = Result<Input>>> = match &dir {
+        Some(dir) => {
+            let (mark_files, stray) = directory::mark_files(dir)?;
+            for finding in stray {
+                checker.emit(finding)?;
+            }
+            Box::new(mark_files.into_iter().map(|file| Ok(Input::File(file))))
+        }
+        None => {
+            let urs = args.marks.iter().map(|ur| {
+                Ok(Input::Ur {
+                    text: ur.trim().to_string(),
+                    source: None,
+                })
+            });
+            let documents = args
+                .files
+                .iter()
+                .map(|path| document_inputs(path))
+                .collect::<Result<Vec<_>>>()?;
+            Box::new(urs.chain(documents.into_iter().flatten()))
+        }
+    };
+
+    let mut highest = Highest::default();
+    loop {
+        let batch = inputs
+            .by_ref()
+            .take(BATCH_SIZE)
+            .collect::<Result<Vec<_>>>()?;
+        if batch.is_empty() {
+            break;
+        }
+        for decoded in decode_batch(batch)? {
+            for finding in decoded.findings {
+                checker.emit(finding)?;
+            }
+            if let Some(mark) = decoded.mark {
+                highest.update(&mark);
+                checker.check(mark, decoded.source)?;
+            }
+        }
+    }
+
+    if let Some(dir) = &dir {
+        for finding in directory::check_generator(dir, &highest) {
+            checker.emit(finding)?;
+        }
+    }
+    checker.finish()
+}
+
+/// Read a document line by line, yielding each UR-like token in it.
+fn document_inputs(
+    path: &Path,
+) -> Result<impl Iterator<Item = Result<Input>> + use<>> {
+    let file = fs::File::open(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let path = path.to_path_buf();
+    Ok(BufReader::new(file).lines().enumerate().flat_map(
+        move |(index, line)| -> Vec<Result<Input>> {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return vec![Err(e.into())],
+            };
+            ur_tokens(&line)
+                .map(|token| {
+                    Ok(Input::Ur {
+                        text: token.to_string(),
+                        source: Some(Source {
+                            path: path.clone(),
+                            line: Some(index + 1),
+                        }),
+                    })
+                })
+                .collect()
+        },
+    ))
+}
+
+/// Decode a batch of inputs on a pool of threads, keeping their order.
+fn decode_batch(batch: Vec<Input>) -> Result<Vec<Decoded>> {
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = batch.len().div_ceil(workers);
+    let chunks: Vec<Result<Vec<Decoded>>> = thread::scope(|scope| {
+        let handles: Vec<_> = batch
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || chunk.iter().map(decode).collect())
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("decoding thread panicked"))
+            .collect()
+    });
+
+    let mut decoded = Vec::with_capacity(batch.len());
+    for chunk in chunks {
+        decoded.extend(chunk?);
+    }
+    Ok(decoded)
+}
+
+fn decode(input: &Input) -> Result<Decoded> {
+    match input {
+        Input::Ur { text, source } => {
+            Ok(match extract::provenance_mark_from_ur(text) {
+                Ok(mark) => Decoded {
+                    mark: Some(mark),
+                    source: source.clone(),
+                    findings: Vec::new(),
+                },
+                Err(e) => Decoded {
+                    mark: None,
+                    source: None,
+                    findings: vec![
+                        Finding::new(IssueClass::Parse, e.to_string())
+                            .at_source(source.clone()),
+                    ],
+                },
+            })
+        }
+        Input::File(file) => {
+            let (mark, findings) = directory::read_mark_file(file)?;
+            Ok(Decoded {
+                mark,
+                source: Some(directory::source(&file.path)),
+                findings,
+            })
+        }
+    }
+}
+
+/// What is remembered about each chain while streaming.
+struct ChainState {
+    /// The mark with the highest sequence number so far.
+    last: ProvenanceMark,
+    /// The mark with the latest date so far.
+    newest: ProvenanceMark,
+    has_genesis: bool,
+    /// The sequence number and statement of the mark that closed the chain,
+    /// if it has been seen.
+    closed: Option<(u32, ChainClosure)>,
+}
+
+/// Checks marks one at a time against the previous mark of their chain.
+struct Checker<'a> {
+    args: &'a CommandArgs,
+    freshness: &'a Freshness,
+    trust: &'a TrustStore,
+    out: &'a mut dyn Write,
+    chains: HashMap<Vec<u8>, ChainState>,
+    /// Chain IDs in the order they were first seen.
+    chain_order: Vec<Vec<u8>>,
+    marks: usize,
+    issues: usize,
+    fatal: Option<IssueClass>,
+}
+
+impl Checker<'_> {
+    fn check(
+        &mut self,
+        mark: ProvenanceMark,
+        source: Option<Source>,
+    ) -> Result<()> {
+        self.marks += 1;
+        if let Some(notice) = self.trust.distrusting(&mark) {
+            let finding = compromise::finding(&mark, notice);
+            self.emit(finding.at_source(source.clone()))?;
+        }
+
+        let Some(state) = self.chains.get_mut(mark.chain_id()) else {
+            if mark.seq() == 0 && !mark.is_genesis() {
+                let issue = ValidationIssue::NonGenesisAtZero;
+                if let Some(finding) = class::issue_finding(&mark, &issue) {
+                    self.emit(finding.at_source(source.clone()))?;
+                }
+            }
+            self.chain_order.push(mark.chain_id().to_vec());
+            self.chains.insert(
+                mark.chain_id().to_vec(),
+                ChainState {
+                    has_genesis: mark.is_genesis(),
+                    closed: ChainClosure::from_mark(&mark)
+                        .map(|closure| (mark.seq(), closure)),
+                    last: mark.clone(),
+                    newest: mark,
+                },
+            );
+            return Ok(());
+        };
+
+        // Exact duplicates are harmless.
+        if mark.hash() == state.last.hash() {
+            return Ok(());
+        }
+
+        // Marks given out of order, as in a list of the newest first, are a
+        // mistake in the input rather than a sign of a fork.
+        if mark.seq() < state.last.seq() {
+            bail!(
+                "Mark {} of chain {} comes after mark {}{}. --stream needs \
+                 each chain's marks in sequence order; sort them, or validate \
+                 without --stream.",
+                mark.seq(),
+                hex::encode(mark.chain_id()),
+                state.last.seq(),
+                source
+                    .map(|source| format!(" at {}", source))
+                    .unwrap_or_default()
+            );
+        }
+
+        let mut findings = Vec::new();
+        if mark.seq() == state.last.seq() {
+            findings.push(
+                Finding::new(
+                    IssueClass::Fork,
+                    format!(
+                        "conflicting marks {} and {} share this sequence \
+                         number",
+                        state.last.identifier(),
+                        mark.identifier()
+                    ),
+                )
+                .on_mark(&mark),
+            );
+        } else {
+            match state.last.precedes_opt(&mark) {
+                Ok(()) => {}
+                Err(provenance_mark::Error::Validation(issue)) => {
+                    findings.extend(class::issue_finding(&mark, &issue));
+                }
+                Err(e) => findings.push(
+                    Finding::new(IssueClass::Mismatch, e.to_string())
+                        .on_mark(&mark),
+                ),
+            }
+            if let Some(max) = self.freshness.max_interval {
+                let pair = [state.last.clone(), mark.clone()];
+                findings.extend(long_intervals(&pair, max).map(
+                    |(_, next, message)| {
+                        Finding::new(IssueClass::Silence, message).on_mark(next)
+                    },
+                ));
+            }
+            if let Some((closing_seq, closure)) = &state.closed {
+                findings.push(
+                    Finding::new(
+                        IssueClass::AfterClose,
+                        closure::message(*closing_seq, closure),
+                    )
+                    .on_mark(&mark),
+                );
+            } else if let Some(closure) = ChainClosure::from_mark(&mark) {
+                state.closed = Some((mark.seq(), closure));
+            }
+            if mark.date() > state.newest.date() {
+                state.newest = mark.clone();
+            }
+            state.last = mark;
+        }
+
+        for finding in findings {
+            self.emit(finding.at_source(source.clone()))?;
+        }
+        Ok(())
+    }
+
+    /// Report the issues that can only be known once every mark has been
+    /// seen, and return the most severe fatal class.
+    fn finish(mut self) -> Result<Option<IssueClass>> {
+        let mut findings = Vec::new();
+        if self.chain_order.len() > 1 {
+            findings.push(Finding::new(
+                IssueClass::MultipleChains,
+                format!("marks belong to {} chains", self.chain_order.len()),
+            ));
+        }
+        for chain_id in &self.chain_order {
+            let state = &self.chains[chain_id];
+            if !state.has_genesis {
+                findings.push(
+                    Finding::new(
+                        IssueClass::MissingGenesis,
+                        "no genesis mark found",
+                    )
+                    .in_chain(hex::encode(chain_id)),
+                );
+            }
+            findings.extend(self.freshness.check_newest(&state.newest));
+        }
+        for finding in findings {
+            self.emit(finding)?;
+        }
+
+        if self.issues > 0 && self.args.format == Format::Text {
+            writeln!(
+                self.out,
+                "Total marks: {}, chains: {}, issues: {}",
+                self.marks,
+                self.chain_order.len(),
+                self.issues
+            )?;
+        }
+        Ok(self.fatal)
+    }
+
+    /// Write an issue as soon as it is found.
+    fn emit(&mut self, finding: Finding) -> Result<()> {
+        self.issues += 1;
+        if self.args.is_fatal(finding.class) {
+            self.fatal = IssueClass::most_severe(
+                self.fatal.into_iter().chain([finding.class]),
+            );
+        }
+
+        let dir = self.args.dir.as_deref();
+        if self.args.format == Format::Text {
+            writeln!(self.out, "{}: {}", finding.label(), finding.describe())?;
+            if self.args.explain {
+                let explanation = self.args.explain(&finding, dir);
+                for line in explanation.lines().skip(1) {
+                    writeln!(self.out, "{}", line)?;
+                }
+            }
+        } else {
+            let mut issue = finding.to_json();
+            if self.args.explain {
+                issue["explanation"] = self.args.explain(&finding, dir).into();
+            }
+            writeln!(self.out, "{}", issue)?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
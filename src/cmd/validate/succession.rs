@@ -0,0 +1,249 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use provenance_mark::{ChainReport, ProvenanceMark, ValidationReport};
+use serde::Serialize;
+
+use super::{
+    Inputs,
+    class::{Finding, IssueClass},
+    directory,
+};
+use crate::{
+    chain_dir::ChainDir,
+    cmd::{
+        close::ChainClosure,
+        succession::{Predecessor, Successor},
+    },
+    config::CHAIN_CONFIG_FILE,
+};
+
+/// One chain in a history of chains that succeed one another.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub chain_id: String,
+    pub first: u32,
+    pub last: u32,
+}
+
+/// The outcome of following the succession links between chains.
+#[derive(Debug, Default)]
+pub struct Succession {
+    /// The chains in order, when every chain validated is linked to the next
+    /// into one history.
+    pub history: Vec<HistoryEntry>,
+    pub findings: Vec<Finding>,
+}
+
+/// Load the marks of each chain that a chain directory succeeds, following
+/// the `predecessor` setting of each `chain.toml` in turn.
+pub fn load_predecessors(path: &Path, inputs: &mut Inputs) {
+    let mut visited = HashSet::new();
+    visited.extend(path.canonicalize().ok());
+    let mut current = path.to_path_buf();
+    loop {
+        let Some(predecessor) = ChainDir::open(&current)
+            .ok()
+            .and_then(|chain_dir| chain_dir.predecessor_path())
+        else {
+            return;
+        };
+        let finding = |message: String| {
+            Finding::new(IssueClass::Succession, message).at_source(Some(
+                directory::source(&current.join(CHAIN_CONFIG_FILE)),
+            ))
+        };
+        let Ok(canonical) = predecessor.canonicalize() else {
+            inputs.findings.push(finding(format!(
+                "predecessor directory not found: {}",
+                predecessor.display()
+            )));
+            return;
+        };
+        if !visited.insert(canonical) {
+            inputs.findings.push(finding(format!(
+                "predecessor {} has already been loaded; the chains succeed \
+                 one another in a loop",
+                predecessor.display()
+            )));
+            return;
+        }
+        if let Err(e) = directory::load(&predecessor, inputs) {
+            inputs.findings.push(finding(e.to_string()));
+            return;
+        }
+        current = predecessor;
+    }
+}
+
+/// Check the succession links between the chains of a report: each genesis
+/// mark that names a predecessor must name its head, and the predecessor
+/// must be closed by the next mark, committing to that genesis mark.
+pub fn check(report: &ValidationReport) -> Succession {
+    let chains: HashMap<String, &ChainReport> = report
+        .chains()
+        .iter()
+        .map(|chain| (chain.chain_id_hex(), chain))
+        .collect();
+
+    let mut findings = Vec::new();
+    let mut links = HashMap::new();
+    for chain in report.chains() {
+        let Some(genesis) = chain.marks().iter().find(|mark| mark.is_genesis())
+        else {
+            continue;
+        };
+        let Some(predecessor) = Predecessor::from_mark(genesis) else {
+            continue;
+        };
+        let predecessor_id = hex::encode(&predecessor.chain_id);
+        let Some(predecessor_chain) = chains.get(&predecessor_id) else {
+            continue;
+        };
+        let problems = link_problems(
+            &predecessor,
+            &predecessor_id,
+            predecessor_chain,
+            genesis,
+        );
+        if problems.is_empty() {
+            links.insert(predecessor_id, chain.chain_id_hex());
+        }
+        findings.extend(problems.into_iter().map(|problem| {
+            Finding::new(IssueClass::Succession, problem).on_mark(genesis)
+        }));
+    }
+
+    // A closing mark that names a successor given here must be named back.
+    for chain in report.chains() {
+        let Some((closing, successor)) = successor(chain) else {
+            continue;
+        };
+        let successor_id = hex::encode(&successor.chain_id);
+        if chains.contains_key(&successor_id)
+            && links.get(&chain.chain_id_hex()) != Some(&successor_id)
+        {
+            findings.push(
+                Finding::new(
+                    IssueClass::Succession,
+                    format!(
+                        "names chain {} as its successor, but that chain's \
+                         genesis mark does not link back to this chain",
+                        successor_id
+                    ),
+                )
+                .on_mark(closing),
+            );
+        }
+    }
+
+    Succession {
+        history: history(report, &chains, &links),
+        findings,
+    }
+}
+
+/// Why the link from a genesis mark back to its predecessor's chain does not
+/// hold, if it does not.
+fn link_problems(
+    predecessor: &Predecessor,
+    predecessor_id: &str,
+    predecessor_chain: &ChainReport,
+    genesis: &ProvenanceMark,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let head = predecessor_chain
+        .marks()
+        .iter()
+        .find(|mark| mark.seq() == predecessor.seq);
+    match head {
+        None => problems.push(format!(
+            "names mark {} of chain {} as its predecessor's head, but that \
+             mark is not present",
+            predecessor.seq, predecessor_id
+        )),
+        Some(head) if !predecessor.matches(head) => problems.push(format!(
+            "names mark {} of chain {} as its predecessor's head, but that \
+             mark differs",
+            predecessor.seq, predecessor_id
+        )),
+        Some(_) => {}
+    }
+    match successor(predecessor_chain) {
+        None => problems.push(format!(
+            "chain {} was not closed in favor of this chain",
+            predecessor_id
+        )),
+        Some((_, successor)) if !successor.matches(genesis) => {
+            problems.push(format!(
+                "chain {} was closed in favor of chain {}",
+                predecessor_id,
+                hex::encode(&successor.chain_id)
+            ))
+        }
+        Some((closing, _)) if closing.seq() != predecessor.seq + 1 => problems
+            .push(format!(
+                "chain {} was closed at mark {}, which does not directly \
+                 follow the head this chain names",
+                predecessor_id,
+                closing.seq()
+            )),
+        Some(_) => {}
+    }
+    problems
+}
+
+/// The first closing mark of a chain that names a successor, with the
+/// successor it names.
+fn successor(chain: &ChainReport) -> Option<(&ProvenanceMark, Successor)> {
+    chain
+        .marks()
+        .iter()
+        .filter_map(|mark| {
+            Some((mark, ChainClosure::from_mark(mark)?.successor?))
+        })
+        .min_by_key(|(mark, _)| mark.seq())
+}
+
+/// The chains in order of succession, if there is more than one and they are
+/// all linked into a single history.
+fn history(
+    report: &ValidationReport,
+    chains: &HashMap<String, &ChainReport>,
+    links: &HashMap<String, String>,
+) -> Vec<HistoryEntry> {
+    if chains.len() < 2 || links.len() != chains.len() - 1 {
+        return Vec::new();
+    }
+    let successors: HashSet<&String> = links.values().collect();
+    let mut first = report
+        .chains()
+        .iter()
+        .map(|chain| chain.chain_id_hex())
+        .filter(|chain_id| !successors.contains(chain_id));
+    let (Some(mut chain_id), None) = (first.next(), first.next()) else {
+        return Vec::new();
+    };
+
+    let mut history = Vec::new();
+    loop {
+        let marks = chains[&chain_id].marks();
+        history.push(HistoryEntry {
+            chain_id: chain_id.clone(),
+            first: marks.iter().map(|mark| mark.seq()).min().unwrap_or(0),
+            last: marks.iter().map(|mark| mark.seq()).max().unwrap_or(0),
+        });
+        match links.get(&chain_id) {
+            Some(next) => chain_id = next.clone(),
+            None => break,
+        }
+    }
+    if history.len() == chains.len() {
+        history
+    } else {
+        Vec::new()
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Args;
+
+use crate::{
+    cmd::{extract, proof},
+    utils::read_argument,
+};
+
+/// Verify offline that a proof bundle from `proof` descends from a known mark.
+#[derive(Debug, Args)]
+#[group(skip)]
+pub struct CommandArgs {
+    /// The proof bundle, as a `ur:envelope`. If not supplied, it is read from
+    /// stdin.
+    bundle: Option<String>,
+
+    /// The mark the proof must descend from, usually the chain's published
+    /// genesis mark. Accepts the same UR forms as `validate`.
+    ///
+    /// The bundle's first mark must be this mark or the mark that directly
+    /// follows it.
+    #[arg(short, long, value_name = "UR")]
+    genesis: String,
+}
+
+impl crate::exec::Exec for CommandArgs {
+    fn exec(&self) -> Result<String> {
+        let anchor = extract::provenance_mark_from_ur(self.genesis.trim())?;
+        let bundle = read_argument(self.bundle.as_deref())?;
+        let marks = proof::read_bundle(bundle.trim())?;
+        let first = &marks[0];
+        let last = &marks[marks.len() - 1];
+
+        // The bundle must start at the anchor or at its direct successor.
+        if first.chain_id() != anchor.chain_id() {
+            bail!("The proof is not for the same chain as the anchor mark.");
+        }
+        if first != &anchor {
+            anchor.precedes_opt(first).map_err(|e| {
+                anyhow!(
+                    "The proof's first mark {} does not follow anchor mark {}: {}",
+                    first.seq(),
+                    anchor.seq(),
+                    e
+                )
+            })?;
+        }
+
+        // Every mark in the bundle must follow the one before it.
+        for pair in marks.windows(2) {
+            pair[0].precedes_opt(&pair[1]).map_err(|e| {
+                anyhow!(
+                    "Mark {} does not follow mark {}: {}",
+                    pair[1].seq(),
+                    pair[0].seq(),
+                    e
+                )
+            })?;
+        }
+
+        Ok(format!(
+            "Proof verified: marks {} through {} of chain {} descend from mark {} ({}).\nLast mark: {}",
+            first.seq(),
+            last.seq(),
+            hex::encode(anchor.chain_id()),
+            anchor.seq(),
+            anchor.bytewords_identifier(true),
+            last.bytewords_identifier(true)
+        ))
+    }
+}
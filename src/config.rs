@@ -0,0 +1,170 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow, bail};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{new::Resolution, print::OutputFormat};
+
+/// The name of a chain's configuration file, kept in the chain directory.
+pub const CHAIN_CONFIG_FILE: &str = "chain.toml";
+
+/// The environment variable naming the chain to use when none is given.
+pub const CHAIN_ENV_VAR: &str = "PROVENANCE_CHAIN";
+
+/// A chain's `chain.toml`. Every setting is optional.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ChainConfig {
+    /// Where the chain's generator is kept, if not in `generator.json` beside
+    /// the marks. A relative path is relative to the chain directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generator: Option<PathBuf>,
+
+    /// The directory of the chain this chain succeeds, as recorded by `new
+    /// --succeeds`. A relative path is relative to the chain directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predecessor: Option<PathBuf>,
+
+    /// The comment for new marks when `next` is not given one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+
+    /// The output format when a command is not given one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+impl ChainConfig {
+    /// Read the `chain.toml` in a chain directory, or the default
+    /// configuration if there is none.
+    pub fn load(dir: &Path) -> Result<Self> {
+        load_toml(&dir.join(CHAIN_CONFIG_FILE))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        fs::write(dir.join(CHAIN_CONFIG_FILE), toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// The user's `config.toml`, in `$XDG_CONFIG_HOME/provenance` or
+/// `~/.config/provenance`. Every setting is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct UserConfig {
+    /// Defaults for every chain, used where a chain's `chain.toml` does not
+    /// give its own.
+    #[serde(default)]
+    pub defaults: Defaults,
+
+    /// Chain directories by name, for use with `--chain`. A relative path is
+    /// relative to the configuration directory.
+    #[serde(default)]
+    pub chains: BTreeMap<String, PathBuf>,
+
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Defaults {
+    /// The resolution of chains created by `new`.
+    pub resolution: Option<String>,
+
+    /// The comment for new marks when `next` is not given one.
+    pub comment: Option<String>,
+
+    /// The output format when a command is not given one.
+    pub format: Option<String>,
+}
+
+impl UserConfig {
+    /// The user's configuration directory, if one can be determined.
+    pub fn dir() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("provenance"))
+    }
+
+    /// Read the user's `config.toml`, or the default configuration if there
+    /// is none.
+    pub fn load() -> Result<Self> {
+        let Some(dir) = Self::dir() else {
+            return Ok(Self::default());
+        };
+        let mut config: Self = load_toml(&dir.join("config.toml"))?;
+        config.dir = dir;
+        Ok(config)
+    }
+
+    /// The directory of the chain with the given name.
+    pub fn chain_path(&self, name: &str) -> Result<PathBuf> {
+        match self.chains.get(name) {
+            Some(path) => Ok(self.dir.join(path)),
+            None => bail!(
+                "No chain named `{}` in {}",
+                name,
+                self.dir.join("config.toml").display()
+            ),
+        }
+    }
+
+    pub fn resolution(&self) -> Result<Option<Resolution>> {
+        parse_setting("resolution", self.defaults.resolution.as_deref())
+    }
+
+    pub fn format(&self) -> Result<Option<OutputFormat>> {
+        parse_setting("format", self.defaults.format.as_deref())
+    }
+}
+
+/// The comment for a new mark: the chain's default, then the user's.
+pub fn default_comment(
+    chain: &ChainConfig,
+    user: &UserConfig,
+) -> Option<String> {
+    chain
+        .comment
+        .clone()
+        .or_else(|| user.defaults.comment.clone())
+}
+
+/// The output format for a command: the chain's default, then the user's.
+pub fn default_format(
+    chain: &ChainConfig,
+    user: &UserConfig,
+) -> Result<Option<OutputFormat>> {
+    match parse_setting("format", chain.format.as_deref())? {
+        Some(format) => Ok(Some(format)),
+        None => user.format(),
+    }
+}
+
+fn parse_setting<T: ValueEnum>(
+    name: &str,
+    value: Option<&str>,
+) -> Result<Option<T>> {
+    value
+        .map(|value| {
+            T::from_str(value, true)
+                .map_err(|_| anyhow!("Invalid `{}` setting: {}", name, value))
+        })
+        .transpose()
+}
+
+fn load_toml<T: Default + for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let text = fs::read_to_string(path)?;
+    toml::from_str(&text)
+        .map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))
+}
@@ -1,5 +1,58 @@
+use std::{fmt, io::Write};
+
 use anyhow::Result;
 
 pub trait Exec {
     fn exec(&self) -> Result<String>;
+
+    /// Write the command's output as it is produced. Commands whose output
+    /// can be large override this so that it is not built up in memory first.
+    fn exec_to(&self, out: &mut dyn Write) -> Result<()> {
+        let output = self.exec()?;
+        if !output.is_empty() {
+            writeln!(out, "{}", output)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error that ends the process with a specific exit code instead of the
+/// default failure code of 1.
+#[derive(Debug)]
+pub struct ExitError {
+    code: i32,
+    message: String,
+    output: Option<String>,
+}
+
+impl ExitError {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            output: None,
+        }
+    }
+
+    /// Attach output to be written to stdout before the process exits.
+    pub fn with_output(mut self, output: impl Into<String>) -> Self {
+        self.output = Some(output.into());
+        self
+    }
+
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    pub fn output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
 }
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ExitError {}
@@ -0,0 +1,51 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// The name of the file in a chain directory that records the idempotency
+/// keys given to `next`.
+pub const IDEMPOTENCY_FILE: &str = "idempotency.json";
+
+/// The sequence number of the mark generated for each idempotency key, kept
+/// in `idempotency.json` as a JSON object.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct IdempotencyKeys {
+    keys: BTreeMap<String, u32>,
+}
+
+impl IdempotencyKeys {
+    /// Read the idempotency keys of a chain directory, or none if it has no
+    /// `idempotency.json`.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(IDEMPOTENCY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)?;
+        serde_json::from_str(&json)
+            .map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(dir.join(IDEMPOTENCY_FILE), json)?;
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<u32> {
+        self.keys.get(key).copied()
+    }
+
+    pub fn insert(&mut self, key: String, seq: u32) {
+        self.keys.insert(key, seq);
+    }
+
+    /// Forget the keys of a mark, returning whether there were any.
+    pub fn forget(&mut self, seq: u32) -> bool {
+        let count = self.keys.len();
+        self.keys.retain(|_, key_seq| *key_seq != seq);
+        self.keys.len() != count
+    }
+}
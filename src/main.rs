@@ -1,18 +1,30 @@
 //! A command line tool for managing provenance mark chains and generating provenance marks. See the main repo [README](https://github.com/BlockchainCommons/provenance-mark-cli-rust/blob/master/README.md).
 
+#[doc(hidden)]
+mod chain_dir;
 #[doc(hidden)]
 mod cmd;
 #[doc(hidden)]
+mod config;
+#[doc(hidden)]
 mod exec;
 #[doc(hidden)]
+mod idempotency;
+#[doc(hidden)]
+mod publications;
+#[doc(hidden)]
 mod styles;
 #[doc(hidden)]
+mod trust_store;
+#[doc(hidden)]
 mod utils;
 
+use std::io::{self, BufWriter, Write};
+
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
-use crate::exec::Exec;
+use crate::exec::{Exec, ExitError};
 
 /// A tool for managing provenance mark chains and generating provenance marks.
 #[derive(Debug, Parser)]
@@ -32,6 +44,16 @@ enum MainCommands {
     Next(cmd::next::CommandArgs),
     Print(cmd::print::CommandArgs),
     Validate(cmd::validate::CommandArgs),
+    Proof(cmd::proof::CommandArgs),
+    VerifyProof(cmd::verify_proof::CommandArgs),
+    Follows(cmd::follows::CommandArgs),
+    Diff(cmd::diff::CommandArgs),
+    Mirror(cmd::mirror::CommandArgs),
+    Status(cmd::status::CommandArgs),
+    Close(cmd::close::CommandArgs),
+    Notice(cmd::notice::CommandArgs),
+    Undo(cmd::undo::CommandArgs),
+    PublishRecord(cmd::publish_record::CommandArgs),
 }
 
 #[doc(hidden)]
@@ -40,15 +62,44 @@ fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
-    let output = match cli.command {
-        MainCommands::New(args) => args.exec(),
-        MainCommands::Next(args) => args.exec(),
-        MainCommands::Print(args) => args.exec(),
-        MainCommands::Validate(args) => args.exec(),
-    };
-    let output = output?;
-    if !output.is_empty() {
-        println!("{}", output);
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let result = match cli.command {
+        MainCommands::New(args) => args.exec_to(&mut stdout),
+        MainCommands::Next(args) => args.exec_to(&mut stdout),
+        MainCommands::Print(args) => args.exec_to(&mut stdout),
+        MainCommands::Validate(args) => args.exec_to(&mut stdout),
+        MainCommands::Proof(args) => args.exec_to(&mut stdout),
+        MainCommands::VerifyProof(args) => args.exec_to(&mut stdout),
+        MainCommands::Follows(args) => args.exec_to(&mut stdout),
+        MainCommands::Diff(args) => args.exec_to(&mut stdout),
+        MainCommands::Mirror(args) => args.exec_to(&mut stdout),
+        MainCommands::Status(args) => args.exec_to(&mut stdout),
+        MainCommands::Close(args) => args.exec_to(&mut stdout),
+        MainCommands::Notice(args) => args.exec_to(&mut stdout),
+        MainCommands::Undo(args) => args.exec_to(&mut stdout),
+        MainCommands::PublishRecord(args) => args.exec_to(&mut stdout),
+    }
+    .and_then(|()| Ok(stdout.flush()?));
+    if let Err(err) = result {
+        // Output cut short by a closed pipe, as with `| head`, is not an
+        // error.
+        if let Some(e) = err.downcast_ref::<io::Error>()
+            && e.kind() == io::ErrorKind::BrokenPipe
+        {
+            return Ok(());
+        }
+        stdout.flush()?;
+        match err.downcast_ref::<ExitError>() {
+            Some(exit) => {
+                if let Some(output) = exit.output() {
+                    writeln!(stdout, "{}", output)?;
+                    stdout.flush()?;
+                }
+                eprintln!("Error: {:?}", err);
+                std::process::exit(exit.code());
+            }
+            None => return Err(err),
+        }
     }
     Ok(())
 }
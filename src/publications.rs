@@ -0,0 +1,79 @@
+use std::{fs, path::Path};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// The name of the file in a chain directory that records which of its marks
+/// have been published.
+pub const PUBLICATIONS_FILE: &str = "publications.json";
+
+/// The publication records of a chain directory's marks, kept in
+/// `publications.json` as a list of objects, each naming the `seq` of a
+/// published mark, the `url` it was published at, and the date it was
+/// published `at`. Records from before `publish-record` may give only the
+/// `seq`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Publications {
+    records: Vec<Publication>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Publication {
+    pub seq: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+}
+
+impl Publications {
+    /// Read the publication records of a chain directory, or none if it has
+    /// no `publications.json`.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(PUBLICATIONS_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)?;
+        serde_json::from_str(&json)
+            .map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(dir.join(PUBLICATIONS_FILE), json)?;
+        Ok(())
+    }
+
+    /// The record of a mark's publication at a URL, if there is one.
+    pub fn find(&self, seq: u32, url: &str) -> Option<&Publication> {
+        self.records.iter().find(|record| {
+            record.seq == seq && record.url.as_deref() == Some(url)
+        })
+    }
+
+    pub fn add(&mut self, record: Publication) {
+        self.records.push(record);
+        self.records.sort_by_key(|record| record.seq);
+    }
+
+    /// The highest sequence number recorded as published, if any.
+    pub fn last_published(&self) -> Option<u32> {
+        self.records.iter().map(|record| record.seq).max()
+    }
+
+    pub fn is_published(&self, seq: u32) -> bool {
+        self.records.iter().any(|record| record.seq == seq)
+    }
+
+    /// Where and when a mark was published, as JSON objects with `url` and
+    /// `at` fields, either of which is null if it was not recorded.
+    pub fn for_mark(&self, seq: u32) -> Vec<serde_json::Value> {
+        self.records
+            .iter()
+            .filter(|record| record.seq == seq)
+            .map(|record| serde_json::json!({"url": record.url, "at": record.at}))
+            .collect()
+    }
+}
@@ -0,0 +1,87 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Result, bail};
+use bc_envelope::prelude::*;
+use provenance_mark::ProvenanceMark;
+
+use crate::{cmd::notice::CompromiseNotice, config::UserConfig};
+
+/// The compromise notices the user has accepted, kept as one `ur:envelope`
+/// file each in the `notices` subdirectory of the user's configuration
+/// directory.
+#[derive(Debug, Default)]
+pub struct TrustStore {
+    notices: Vec<CompromiseNotice>,
+    /// Why each notice that could not be read was skipped.
+    problems: Vec<String>,
+}
+
+impl TrustStore {
+    /// Where the notices are kept, if a configuration directory can be
+    /// determined.
+    pub fn dir() -> Option<PathBuf> {
+        UserConfig::dir().map(|dir| dir.join("notices"))
+    }
+
+    /// Read every notice in the trust store. An empty store is returned if
+    /// there is none. A notice that cannot be read is skipped and listed in
+    /// `problems`, so that one bad file does not stop every validation.
+    pub fn load() -> Self {
+        let mut store = Self::default();
+        let Some(dir) = Self::dir().filter(|dir| dir.is_dir()) else {
+            return store;
+        };
+        let mut paths: Vec<PathBuf> = match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "ur"))
+                .collect(),
+            Err(e) => {
+                store.problems.push(format!("{}: {}", dir.display(), e));
+                return store;
+            }
+        };
+        paths.sort();
+        for path in paths {
+            let notice = fs::read_to_string(&path)
+                .map_err(Into::into)
+                .and_then(|ur| CompromiseNotice::from_ur_string(ur.trim()));
+            match notice {
+                Ok(notice) => store.notices.push(notice),
+                Err(e) => {
+                    store.problems.push(format!("{}: {}", path.display(), e))
+                }
+            }
+        }
+        store
+    }
+
+    /// Why each notice that could not be read was skipped.
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    /// Store a notice, named for its digest, returning its path and whether
+    /// it was not already stored.
+    pub fn add(notice: &CompromiseNotice) -> Result<(PathBuf, bool)> {
+        let Some(dir) = Self::dir() else {
+            bail!("Cannot determine the configuration directory; set HOME");
+        };
+        fs::create_dir_all(&dir)?;
+        let envelope = notice.to_envelope();
+        let path = dir.join(format!("{}.ur", envelope.digest().hex()));
+        if path.exists() {
+            return Ok((path, false));
+        }
+        fs::write(&path, envelope.ur_string())?;
+        Ok((path, true))
+    }
+
+    /// The first notice that distrusts the mark, if any.
+    pub fn distrusting(
+        &self,
+        mark: &ProvenanceMark,
+    ) -> Option<&CompromiseNotice> {
+        self.notices.iter().find(|notice| notice.distrusts(mark))
+    }
+}
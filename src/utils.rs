@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use std::{env, io::Read, path::PathBuf};
+use std::{env, io::Read, path::PathBuf, time::Duration};
 
 use anyhow::{Result, bail};
 use glob::glob;
@@ -23,7 +23,14 @@ pub fn read_new_path(path: &PathBuf) -> Result<PathBuf> {
             path.clone()
         }
     } else {
-        matches.remove(0)
+        // A relative pattern yields relative matches, which are resolved
+        // too.
+        let path = matches.remove(0);
+        if path.is_relative() {
+            env::current_dir()?.join(path)
+        } else {
+            path
+        }
     };
 
     let cleaned_path = effective_path.components().collect::<PathBuf>();
@@ -53,3 +60,64 @@ pub fn read_argument(argument: Option<&str>) -> Result<String> {
     }
     Ok(string)
 }
+
+/// Parse a duration such as `30d`, `12h`, or `1w2d`. The units are `s`, `m`,
+/// `h`, `d`, and `w`; a bare number is a count of seconds.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err("duration string is empty".to_string());
+    }
+    if let Ok(seconds) = trimmed.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut seconds: u64 = 0;
+    let mut digits = String::new();
+    for c in trimmed.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => {
+                return Err(format!(
+                    "invalid duration unit '{c}' in '{input}'"
+                ));
+            }
+        };
+        let count: u64 = digits
+            .parse()
+            .map_err(|_| format!("missing number before '{c}' in '{input}'"))?;
+        seconds = count
+            .checked_mul(unit)
+            .and_then(|s| seconds.checked_add(s))
+            .ok_or_else(|| format!("duration '{input}' is too large"))?;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err(format!("missing unit after '{digits}' in '{input}'"));
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Format a number of seconds in the largest whole units, e.g. `35d 2h`.
+pub fn format_duration(seconds: f64) -> String {
+    let mut remaining = seconds.max(0.0).round() as u64;
+    let mut parts = Vec::new();
+    for (unit, size) in [("d", 24 * 60 * 60), ("h", 60 * 60), ("m", 60)] {
+        if remaining >= size {
+            parts.push(format!("{}{}", remaining / size, unit));
+            remaining %= size;
+        }
+    }
+    if remaining > 0 || parts.is_empty() {
+        parts.push(format!("{}s", remaining));
+    }
+    parts.join(" ")
+}
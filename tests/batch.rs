@@ -0,0 +1,132 @@
+mod common;
+
+use std::fs;
+
+use common::{create_chain, provenance, read_mark};
+use tempfile::TempDir;
+
+#[test]
+fn test_batch_from_csv() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+    let manifest = temp_dir.path().join("marks.csv");
+    fs::write(
+        &manifest,
+        indoc::indoc! {r#"
+            comment,date,info
+            "Print 1, signed",2023-06-21T12:00:00Z,
+            Print 2,2023-06-22T12:00:00Z,
+            ,2023-06-23T12:00:00Z,
+        "#},
+    )
+    .unwrap();
+
+    let output = provenance()
+        .arg("next")
+        .arg(&chain)
+        .arg("--batch")
+        .arg(&manifest)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stderr.contains("Mark 3 written to:"), "{}", stderr);
+    let marks: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(marks.as_array().unwrap().len(), 3);
+
+    assert_eq!(read_mark(&chain, 1)["comment"], "Print 1, signed");
+    assert_eq!(read_mark(&chain, 2)["comment"], "Print 2");
+    assert_eq!(read_mark(&chain, 3)["comment"], "Blank.");
+
+    provenance()
+        .args(["validate", "--dir"])
+        .arg(&chain)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_batch_from_jsonl_as_urs() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+    let manifest = temp_dir.path().join("marks.jsonl");
+    fs::write(
+        &manifest,
+        indoc::indoc! {r#"
+            {"comment": "First", "date": "2023-06-21T12:00:00Z"}
+
+            {"comment": "Second", "date": "2023-06-22T12:00:00Z"}
+        "#},
+    )
+    .unwrap();
+
+    let output = provenance()
+        .arg("next")
+        .arg(&chain)
+        .arg("--batch")
+        .arg(&manifest)
+        .args(["--format", "ur", "--quiet"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let urs: Vec<&str> = stdout.lines().collect();
+    assert_eq!(urs.len(), 2);
+    assert!(urs.iter().all(|ur| ur.starts_with("ur:provenance/")));
+    assert_eq!(read_mark(&chain, 2)["comment"], "Second");
+}
+
+#[test]
+fn test_batch_with_a_bad_row_changes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+    let generator = fs::read_to_string(chain.join("generator.json")).unwrap();
+
+    for (name, manifest, problem) in [
+        (
+            "bad-info.jsonl",
+            indoc::indoc! {r#"
+                {"comment": "Fine"}
+                {"comment": "Broken", "info": "not hex or a UR"}
+            "#},
+            "Row 2 of",
+        ),
+        (
+            "out-of-order.csv",
+            indoc::indoc! {r#"
+                comment,date
+                Later,2023-06-22T12:00:00Z
+                Earlier,2023-06-21T12:00:00Z
+            "#},
+            "before the previous row's",
+        ),
+        (
+            "unknown-column.csv",
+            "comment,colour\nRed,red\n",
+            "Row 1 of",
+        ),
+    ] {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, manifest).unwrap();
+        let output = provenance()
+            .arg("next")
+            .arg(&chain)
+            .arg("--batch")
+            .arg(&path)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success(), "{}", name);
+        assert!(stderr.contains(problem), "{}: {}", name, stderr);
+        assert!(!chain.join("marks/mark-1.json").exists(), "{}", name);
+        assert_eq!(
+            fs::read_to_string(chain.join("generator.json")).unwrap(),
+            generator
+        );
+    }
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{create_chain, provenance};
+use tempfile::TempDir;
+
+fn create_closed_chain(path: &Path) {
+    create_chain(path, 0);
+    provenance()
+        .arg("close")
+        .arg(path)
+        .args(["--reason", "Project retired", "--quiet"])
+        .args(["--date", "2023-06-21T12:00:00Z"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_close_refuses_further_marks() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_closed_chain(&chain);
+    assert!(chain.join("marks/mark-1.json").is_file());
+
+    provenance()
+        .arg("next")
+        .arg(&chain)
+        .arg("--quiet")
+        .assert()
+        .failure();
+    provenance()
+        .arg("close")
+        .arg(&chain)
+        .args(["--reason", "Again"])
+        .assert()
+        .failure();
+    assert!(!chain.join("marks/mark-2.json").exists());
+
+    provenance()
+        .args(["validate", "--dir"])
+        .arg(&chain)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_validate_reports_marks_after_close() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_closed_chain(&chain);
+
+    // Someone with a copy of the generator keeps issuing marks.
+    let generator_path = chain.join("generator.json");
+    let mut generator: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&generator_path).unwrap())
+            .unwrap();
+    generator.as_object_mut().unwrap().remove("closed");
+    fs::write(&generator_path, generator.to_string()).unwrap();
+    provenance()
+        .arg("next")
+        .arg(&chain)
+        .args(["--date", "2023-06-22T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+
+    for stream in [false, true] {
+        let mut cmd = provenance();
+        cmd.args(["validate", "--dir"]).arg(&chain);
+        if stream {
+            cmd.arg("--stream");
+        }
+        let output = cmd.output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(14), "{}{}", stdout, stderr);
+        assert!(
+            format!("{}{}", stdout, stderr)
+                .contains("closed at mark 1 (Project retired)"),
+            "{}{}",
+            stdout,
+            stderr
+        );
+    }
+}
+
+#[test]
+fn test_close_checks_its_date_and_uses_the_default_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+
+    let output = provenance()
+        .arg("close")
+        .arg(&chain)
+        .args(["--reason", "Retired", "--date", "2023-06-19T12:00:00Z"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("is before the chain's last mark"),
+        "{}",
+        stderr
+    );
+    assert!(!chain.join("marks/mark-1.json").exists());
+
+    fs::write(chain.join("chain.toml"), "format = \"ur\"\n").unwrap();
+    let output = provenance()
+        .arg("close")
+        .arg(&chain)
+        .args(["--reason", "Retired", "--date", "2023-06-21T12:00:00Z"])
+        .arg("--quiet")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("ur:provenance/"), "{}", stdout);
+}
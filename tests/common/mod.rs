@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+
+use std::{fs, path::Path, process::Output};
+
+use assert_cmd::{Command, cargo::cargo_bin_cmd};
+
+/// The `provenance` command, kept apart from the user's configuration
+/// directory and `PROVENANCE_CHAIN` so that results do not depend on the
+/// machine the tests run on.
+pub fn provenance() -> Command {
+    let mut cmd = cargo_bin_cmd!("provenance");
+    cmd.env(
+        "XDG_CONFIG_HOME",
+        concat!(env!("CARGO_TARGET_TMPDIR"), "/no-config"),
+    )
+    .env_remove("PROVENANCE_CHAIN");
+    cmd
+}
+
+/// The `provenance` command with `config_home` as its configuration
+/// directory.
+pub fn provenance_with_config(config_home: &Path) -> Command {
+    let mut cmd = provenance();
+    cmd.env("XDG_CONFIG_HOME", config_home);
+    cmd
+}
+
+/// Creates a chain at `path` with its genesis mark dated 2023-06-20 and
+/// `last` further marks, one a day after it.
+pub fn create_chain(path: &Path, last: u32) {
+    create_chain_with(path, &[], last);
+}
+
+/// Like [`create_chain`], passing `args` to `new`.
+pub fn create_chain_with(path: &Path, args: &[&str], last: u32) {
+    provenance()
+        .arg("new")
+        .arg(path)
+        .args(args)
+        .args(["--date", "2023-06-20T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+    for i in 1..=last {
+        provenance()
+            .arg("next")
+            .arg(path)
+            .args(["--date", &format!("2023-06-{}T12:00:00Z", 20 + i)])
+            .arg("--quiet")
+            .assert()
+            .success();
+    }
+}
+
+/// Runs `next` on `chain` with `args`.
+pub fn next(chain: &Path, args: &[&str]) -> Output {
+    provenance()
+        .arg("next")
+        .arg(chain)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Runs `cmd`, which must succeed, and returns what it wrote to stdout.
+pub fn run(cmd: &mut Command) -> String {
+    let output = cmd.output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The chain's marks as URs, genesis first.
+pub fn mark_urs(path: &Path) -> Vec<String> {
+    run(provenance().arg("print").arg(path).args(["--format", "ur"]))
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Mark `seq` of the chain as stored in its `marks` directory.
+pub fn read_mark(chain: &Path, seq: u32) -> serde_json::Value {
+    let path = chain.join(format!("marks/mark-{}.json", seq));
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
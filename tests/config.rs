@@ -0,0 +1,193 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::provenance_with_config;
+use tempfile::TempDir;
+
+fn write_user_config(config_home: &Path, config: &str) {
+    let dir = config_home.join("provenance");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("config.toml"), config).unwrap();
+}
+
+#[test]
+fn test_named_chain_and_user_defaults() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path();
+    write_user_config(
+        config_home,
+        indoc::indoc! {r#"
+            [defaults]
+            resolution = "low"
+            format = "ur"
+
+            [chains]
+            releases = "releases"
+        "#},
+    );
+
+    let output = provenance_with_config(config_home)
+        .args(["new", "--chain", "releases", "--quiet"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("ur:"));
+    let chain = config_home.join("provenance/releases");
+    assert!(chain.join("marks/mark-0.json").is_file());
+
+    let generator: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(chain.join("generator.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(generator["res"], 0);
+
+    // The environment variable names the chain when none is given.
+    let output = provenance_with_config(config_home)
+        .env("PROVENANCE_CHAIN", "releases")
+        .args(["next", "--quiet"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("ur:"));
+    assert!(chain.join("marks/mark-1.json").is_file());
+}
+
+#[test]
+fn test_chain_defaults() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let chain = temp_dir.path().join("chain");
+    provenance_with_config(&config_home)
+        .arg("new")
+        .arg(&chain)
+        .arg("--quiet")
+        .assert()
+        .success();
+    fs::write(
+        chain.join("chain.toml"),
+        "comment = \"Release.\"\nformat = \"json\"\n",
+    )
+    .unwrap();
+
+    let output = provenance_with_config(&config_home)
+        .arg("next")
+        .arg(&chain)
+        .arg("--quiet")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let mark: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(mark["comment"], "Release.");
+
+    // Options on the command line win over the chain's defaults.
+    let output = provenance_with_config(&config_home)
+        .arg("next")
+        .arg(&chain)
+        .args(["--comment", "Hotfix.", "--format", "ur", "--quiet"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("ur:"));
+    let mark: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(chain.join("marks/mark-2.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(mark["comment"], "Hotfix.");
+}
+
+#[test]
+fn test_missing_chain() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path();
+
+    let output = provenance_with_config(config_home)
+        .arg("next")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No chain given"), "{}", stderr);
+
+    let output = provenance_with_config(config_home)
+        .args(["print", "--chain", "unknown"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No chain named `unknown`"), "{}", stderr);
+}
+
+#[test]
+fn test_named_chain_for_validate_mirror_and_diff() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path();
+    write_user_config(
+        config_home,
+        indoc::indoc! {r#"
+            [chains]
+            releases = "releases"
+            mirror = "mirror"
+        "#},
+    );
+    let chain = config_home.join("provenance/releases");
+    provenance_with_config(config_home)
+        .args(["new", "--chain", "releases", "--quiet"])
+        .args(["--date", "2023-06-20T12:00:00Z"])
+        .assert()
+        .success();
+    provenance_with_config(config_home)
+        .args(["next", "--chain", "releases", "--quiet"])
+        .args(["--date", "2023-06-21T12:00:00Z"])
+        .assert()
+        .success();
+    let output = provenance_with_config(config_home)
+        .arg("print")
+        .arg(&chain)
+        .args(["--format", "ur"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let urs: Vec<&str> = stdout.lines().collect();
+
+    provenance_with_config(config_home)
+        .args(["mirror", "init", "--chain", "mirror", "--genesis", urs[0]])
+        .assert()
+        .success();
+    // Without a path, the marks to add follow `--`.
+    provenance_with_config(config_home)
+        .args(["mirror", "add", "--chain", "mirror", "--", urs[1]])
+        .assert()
+        .success();
+    assert!(
+        config_home
+            .join("provenance/mirror/marks/mark-1.json")
+            .is_file()
+    );
+
+    provenance_with_config(config_home)
+        .args(["validate", "--chain", "mirror"])
+        .assert()
+        .success();
+    provenance_with_config(config_home)
+        .env("PROVENANCE_CHAIN", "releases")
+        .arg("validate")
+        .assert()
+        .success();
+    let output = provenance_with_config(config_home)
+        .args(["diff", "--chain", "releases"])
+        .arg(config_home.join("provenance/mirror"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Identical marks: 2"), "{}", stdout);
+
+    let output = provenance_with_config(config_home)
+        .arg("validate")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Nothing to validate"), "{}", stderr);
+}
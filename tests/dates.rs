@@ -0,0 +1,85 @@
+mod common;
+
+use common::{create_chain, next, provenance};
+use tempfile::TempDir;
+
+#[test]
+fn test_date_before_head_is_refused_without_force() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+
+    let output = next(&chain, &["--date", "2023-06-19T12:00:00Z"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("is before the chain's last mark"),
+        "{}",
+        stderr
+    );
+    assert!(!chain.join("marks/mark-1.json").exists());
+
+    let output = next(&chain, &["--date", "2023-06-19T12:00:00Z", "--force"]);
+    assert!(output.status.success());
+    assert!(chain.join("marks/mark-1.json").exists());
+}
+
+#[test]
+fn test_date_too_far_in_the_future_is_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+
+    let output = next(&chain, &["--date", "2099-01-01T00:00:00Z"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("more than --max-skew"), "{}", stderr);
+
+    let output = next(
+        &chain,
+        &["--date", "2099-01-01T00:00:00Z", "--max-skew", "100w"],
+    );
+    assert!(!output.status.success());
+
+    let output = next(
+        &chain,
+        &["--date", "2099-01-01T00:00:00Z", "--max-skew", "5000w"],
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_date_the_resolution_cannot_encode_is_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    let output = provenance()
+        .arg("new")
+        .arg(&chain)
+        .args(["--resolution", "low", "--date", "2020-01-01", "--force"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("cannot be encoded at low resolution"),
+        "{}",
+        stderr
+    );
+    assert!(!chain.exists());
+}
+
+#[test]
+fn test_stored_date_is_shown() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    let output = provenance()
+        .arg("new")
+        .arg(&chain)
+        .args(["--resolution", "low", "--date", "2023-06-20T12:34:56Z"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("(dated 2023-06-20"), "{}", stdout);
+    assert!(!stdout.contains("(dated 2023-06-20T12:34:56"), "{}", stdout);
+}
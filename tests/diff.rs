@@ -0,0 +1,138 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::provenance;
+use tempfile::TempDir;
+
+const SEED: &str = "Jgk3vBEDvOjpQtjGDLu3kNQpIEPwg+HDNCL32dvFAS0=";
+
+fn create_chain(path: &Path, genesis_date: &str, last: u32) {
+    provenance()
+        .arg("new")
+        .arg(path)
+        .args(["--seed", SEED, "--date", genesis_date, "--quiet"])
+        .assert()
+        .success();
+    for i in 1..=last {
+        provenance()
+            .arg("next")
+            .arg(path)
+            .args(["--date", &format!("2023-06-{}T12:00:00Z", 20 + i)])
+            .args(["--comment", &format!("Mark {}", i)])
+            .arg("--quiet")
+            .assert()
+            .success();
+    }
+}
+
+fn copy_chain(from: &Path, to: &Path) {
+    fs::create_dir_all(to.join("marks")).unwrap();
+    fs::copy(from.join("generator.json"), to.join("generator.json")).unwrap();
+    for entry in fs::read_dir(from.join("marks")).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), to.join("marks").join(entry.file_name()))
+            .unwrap();
+    }
+}
+
+fn set_comment(path: &Path, seq: u32, comment: &str) {
+    let mark_path = path.join("marks").join(format!("mark-{}.json", seq));
+    let mut json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&mark_path).unwrap()).unwrap();
+    json["comment"] = comment.into();
+    fs::write(&mark_path, json.to_string()).unwrap();
+}
+
+fn run_diff(a: &Path, b: &Path, json: bool) -> (bool, String) {
+    let mut cmd = provenance();
+    cmd.arg("diff").arg(a).arg(b);
+    if json {
+        cmd.args(["--format", "json"]);
+    }
+    let output = cmd.output().expect("Failed to execute command");
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    )
+}
+
+#[test]
+fn test_identical_copies() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = temp_dir.path().join("a");
+    let b = temp_dir.path().join("b");
+    create_chain(&a, "2023-06-20T12:00:00Z", 3);
+    copy_chain(&a, &b);
+
+    let (success, output) = run_diff(&a, &b, false);
+    assert!(success, "Output: {}", output);
+    assert!(output.contains("Identical marks: 4"), "Output: {}", output);
+    assert!(
+        output.contains("The copies are identical."),
+        "Output: {}",
+        output
+    );
+}
+
+#[test]
+fn test_missing_marks_and_comments() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = temp_dir.path().join("a");
+    let b = temp_dir.path().join("b");
+    create_chain(&a, "2023-06-20T12:00:00Z", 3);
+    copy_chain(&a, &b);
+    fs::remove_file(b.join("marks/mark-3.json")).unwrap();
+    set_comment(&b, 1, "Edited");
+
+    let (success, output) = run_diff(&a, &b, true);
+    assert!(!success, "Output: {}", output);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["identical"], 3);
+    assert_eq!(json["onlyInA"][0]["seq"], 3);
+    assert_eq!(json["onlyInB"].as_array().unwrap().len(), 0);
+    assert_eq!(json["comments"][0]["seq"], 1);
+    assert_eq!(json["comments"][0]["a"], "Mark 1");
+    assert_eq!(json["comments"][0]["b"], "Edited");
+    assert_eq!(json["forks"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_fork_against_bundle() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = temp_dir.path().join("a");
+    let b = temp_dir.path().join("b");
+    // The same seed with a different genesis date forks the chain at mark 0.
+    create_chain(&a, "2023-06-20T12:00:00Z", 1);
+    create_chain(&b, "2023-06-19T12:00:00Z", 1);
+
+    let output = provenance().arg("proof").arg(&b).output().unwrap();
+    assert!(output.status.success());
+    let bundle_path = temp_dir.path().join("proof.txt");
+    fs::write(&bundle_path, output.stdout).unwrap();
+
+    let (success, output) = run_diff(&a, &bundle_path, false);
+    assert!(!success, "Output: {}", output);
+    assert!(output.contains("Forks"), "Output: {}", output);
+    assert!(output.contains("  0: A "), "Output: {}", output);
+}
+
+#[test]
+fn test_unrelated_chains_are_not_forks() {
+    let temp_dir = TempDir::new().unwrap();
+    let a = temp_dir.path().join("a");
+    let b = temp_dir.path().join("b");
+    create_chain(&a, "2023-06-20T12:00:00Z", 1);
+    provenance()
+        .arg("new")
+        .arg(&b)
+        .args(["--date", "2023-06-20T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+
+    let output = provenance().arg("diff").arg(&a).arg(&b).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("different chains"), "{}", stderr);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Forks"));
+}
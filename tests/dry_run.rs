@@ -0,0 +1,85 @@
+mod common;
+
+use std::fs;
+
+use common::provenance;
+use tempfile::TempDir;
+
+#[test]
+fn test_next_dry_run_writes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    provenance()
+        .arg("new")
+        .arg(&chain)
+        .args(["--date", "2023-06-20T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+    let generator = fs::read_to_string(chain.join("generator.json")).unwrap();
+
+    let output = provenance()
+        .arg("next")
+        .arg(&chain)
+        .args(["--date", "2023-06-21T12:00:00Z", "--dry-run"])
+        .args(["--format", "ur"])
+        .output()
+        .unwrap();
+    let preview = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stderr.contains("Mark 1 would be written to:"), "{}", stderr);
+    assert!(
+        stderr.contains("Dry run: nothing was written."),
+        "{}",
+        stderr
+    );
+    assert!(!chain.join("marks/mark-1.json").exists());
+    assert_eq!(
+        fs::read_to_string(chain.join("generator.json")).unwrap(),
+        generator
+    );
+
+    // The real mark matches the preview when nothing else changes.
+    let output = provenance()
+        .arg("next")
+        .arg(&chain)
+        .args(["--date", "2023-06-21T12:00:00Z", "--quiet"])
+        .args(["--format", "ur"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), preview);
+    assert!(chain.join("marks/mark-1.json").is_file());
+}
+
+#[test]
+fn test_new_dry_run_creates_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+
+    let output = provenance()
+        .arg("new")
+        .arg(&chain)
+        .args(["--date", "2023-06-20T12:00:00Z", "--dry-run", "--quiet"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(
+        stdout.contains("Dry run: nothing was written."),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("new random seed"), "{}", stdout);
+    assert!(!stdout.contains("would be created"), "{}", stdout);
+    assert!(!chain.exists());
+
+    // A dry run still refuses what the real command would refuse.
+    fs::create_dir(&chain).unwrap();
+    provenance()
+        .arg("new")
+        .arg(&chain)
+        .arg("--dry-run")
+        .assert()
+        .failure();
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use bc_envelope::prelude::*;
+use bc_ur::UREncodable;
+use chrono::TimeZone;
+use common::provenance;
+use provenance_mark::{ProvenanceMarkGenerator, ProvenanceMarkResolution};
+
+fn mark_urs(count: usize, passphrase: &str) -> Vec<String> {
+    provenance_mark::register_tags();
+
+    let mut generator = ProvenanceMarkGenerator::new_with_passphrase(
+        ProvenanceMarkResolution::Quartile,
+        passphrase,
+    );
+    (0..count)
+        .map(|i| {
+            let date = Date::from_datetime(
+                chrono::Utc
+                    .with_ymd_and_hms(2023, 6, 20 + i as u32, 12, 0, 0)
+                    .single()
+                    .unwrap(),
+            );
+            generator.next(date, None::<String>).ur().to_string()
+        })
+        .collect()
+}
+
+fn run_follows(a: &str, b: &str, json: bool) -> (bool, String) {
+    let mut cmd = provenance();
+    cmd.args(["follows", a, b]);
+    if json {
+        cmd.args(["--format", "json"]);
+    }
+    let output = cmd.output().expect("Failed to execute command");
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    )
+}
+
+#[test]
+fn test_direct_successor() {
+    let urs = mark_urs(2, "test");
+    let (success, output) = run_follows(&urs[0], &urs[1], false);
+    assert!(success, "Output: {}", output);
+    assert!(output.contains("Same chain: yes"), "Output: {}", output);
+    assert!(
+        output.contains("Direct successor: yes"),
+        "Output: {}",
+        output
+    );
+    assert!(output.contains("Marks in between: 0"), "Output: {}", output);
+    assert!(
+        output.contains("Dates consistent: yes"),
+        "Output: {}",
+        output
+    );
+}
+
+#[test]
+fn test_marks_in_between() {
+    let urs = mark_urs(4, "test");
+    let (success, output) = run_follows(&urs[0], &urs[3], true);
+    assert!(!success, "Output: {}", output);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["sameChain"], true);
+    assert_eq!(json["directSuccessor"], false);
+    assert_eq!(json["marksBetween"], 2);
+    assert_eq!(json["datesConsistent"], true);
+}
+
+#[test]
+fn test_wrong_order_and_other_chain() {
+    let urs = mark_urs(2, "test");
+    let (success, output) = run_follows(&urs[1], &urs[0], false);
+    assert!(!success);
+    assert!(
+        output.contains("mark 0 does not come after mark 1"),
+        "Output: {}",
+        output
+    );
+
+    let other = mark_urs(2, "other");
+    let (success, output) = run_follows(&urs[0], &other[1], false);
+    assert!(!success);
+    assert!(output.contains("Same chain: no"), "Output: {}", output);
+    assert!(
+        output.contains("Dates consistent: n/a"),
+        "Output: {}",
+        output
+    );
+
+    let (_, output) = run_follows(&urs[0], &other[1], true);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["datesConsistent"], serde_json::Value::Null);
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use std::fs;
+
+use common::provenance;
+use tempfile::TempDir;
+
+#[test]
+fn test_generator_outside_chain_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    let vault = temp_dir.path().join("vault");
+    fs::create_dir(&vault).unwrap();
+    let generator = vault.join("chain-generator.json");
+
+    provenance()
+        .arg("new")
+        .arg(&chain)
+        .arg("--generator")
+        .arg(&generator)
+        .args(["--date", "2023-06-20T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+    assert!(generator.is_file());
+    assert!(!chain.join("generator.json").exists());
+    let config = fs::read_to_string(chain.join("chain.toml")).unwrap();
+    assert!(config.contains("generator = "), "{}", config);
+
+    // `next` finds the generator through `chain.toml`.
+    provenance()
+        .arg("next")
+        .arg(&chain)
+        .args(["--date", "2023-06-21T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+    let generator_json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&generator).unwrap()).unwrap();
+    assert_eq!(generator_json["nextSeq"], 2);
+
+    provenance()
+        .arg("validate")
+        .arg("--dir")
+        .arg(&chain)
+        .assert()
+        .success();
+
+    // The marks can still be printed while the generator is unavailable.
+    fs::rename(&generator, vault.join("moved.json")).unwrap();
+    let output = provenance()
+        .arg("print")
+        .arg(&chain)
+        .args(["--format", "ur"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 2);
+    provenance()
+        .arg("next")
+        .arg(&chain)
+        .arg("--quiet")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_generator_option_overrides_location() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    provenance()
+        .arg("new")
+        .arg(&chain)
+        .args(["--date", "2023-06-20T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+
+    let generator = temp_dir.path().join("generator.json");
+    fs::rename(chain.join("generator.json"), &generator).unwrap();
+
+    provenance()
+        .arg("next")
+        .arg(&chain)
+        .arg("--generator")
+        .arg(&generator)
+        .args(["--date", "2023-06-21T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+    assert!(chain.join("marks/mark-1.json").is_file());
+    assert!(!chain.join("generator.json").exists());
+}
+
+#[test]
+fn test_new_refuses_existing_generator() {
+    let temp_dir = TempDir::new().unwrap();
+    let generator = temp_dir.path().join("generator.json");
+    fs::write(&generator, "{}").unwrap();
+
+    provenance()
+        .arg("new")
+        .arg(temp_dir.path().join("chain"))
+        .arg("--generator")
+        .arg(&generator)
+        .arg("--quiet")
+        .assert()
+        .failure();
+    assert!(!temp_dir.path().join("chain").exists());
+}
+
+#[test]
+fn test_relative_generator_option_is_relative_to_current_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    provenance()
+        .current_dir(temp_dir.path())
+        .args(["new", "chain", "--date", "2023-06-20T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+    fs::create_dir(temp_dir.path().join("vault")).unwrap();
+    fs::rename(
+        temp_dir.path().join("chain/generator.json"),
+        temp_dir.path().join("vault/gen.json"),
+    )
+    .unwrap();
+
+    provenance()
+        .current_dir(temp_dir.path())
+        .args(["next", "chain", "--generator", "vault/gen.json"])
+        .args(["--date", "2023-06-21T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("chain/marks/mark-1.json").is_file());
+    assert!(!temp_dir.path().join("chain/vault").exists());
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{create_chain, provenance, read_mark};
+use tempfile::TempDir;
+
+fn heartbeat(chain: &Path, date: &str) -> std::process::Output {
+    provenance()
+        .arg("next")
+        .arg(chain)
+        .args(["--if-older-than", "7d", "--date", date])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_recent_head_generates_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+    let generator = fs::read_to_string(chain.join("generator.json")).unwrap();
+
+    let output = heartbeat(&chain, "2023-06-23T12:00:00Z");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("Mark 0 is 3d old, newer than 7d; no mark"),
+        "{}",
+        stdout
+    );
+    assert!(!chain.join("marks/mark-1.json").exists());
+    assert_eq!(
+        fs::read_to_string(chain.join("generator.json")).unwrap(),
+        generator
+    );
+}
+
+#[test]
+fn test_old_head_generates_a_heartbeat() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+
+    let output = heartbeat(&chain, "2023-06-28T12:00:00Z");
+    assert!(output.status.success());
+    assert_eq!(read_mark(&chain, 1)["comment"], "Heartbeat.");
+
+    // The heartbeat resets the clock.
+    let output = heartbeat(&chain, "2023-06-30T12:00:00Z");
+    assert!(output.status.success());
+    assert!(!chain.join("marks/mark-2.json").exists());
+
+    let output = provenance()
+        .args(["validate", "--dir"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Heartbeat marks:"), "{}", stdout);
+}
+
+#[test]
+fn test_if_older_than_conflicts_with_info() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+    provenance()
+        .arg("next")
+        .arg(&chain)
+        .args(["--if-older-than", "7d", "--info", "00"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use std::fs;
+
+use common::{create_chain, next, provenance};
+use tempfile::TempDir;
+
+#[test]
+fn test_repeated_key_returns_the_same_mark() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+    let args = [
+        "--comment",
+        "Build 1234",
+        "--idempotency-key",
+        "build-1234",
+        "--format",
+        "ur",
+    ];
+
+    let first = next(&chain, &args);
+    assert!(first.status.success());
+    let generator = fs::read_to_string(chain.join("generator.json")).unwrap();
+
+    let second = next(&chain, &args);
+    let stderr = String::from_utf8_lossy(&second.stderr);
+    assert!(second.status.success(), "{}", stderr);
+    assert!(
+        stderr.contains(
+            "Mark 1 was already generated for idempotency key `build-1234`"
+        ),
+        "{}",
+        stderr
+    );
+    assert_eq!(first.stdout, second.stdout);
+    assert_eq!(
+        fs::read_to_string(chain.join("generator.json")).unwrap(),
+        generator
+    );
+    assert!(!chain.join("marks/mark-2.json").exists());
+
+    // A dry run returns the existing mark, not a preview.
+    let dry_run = next(&chain, &[&args[..], &["--dry-run"]].concat());
+    let stderr = String::from_utf8_lossy(&dry_run.stderr);
+    assert!(dry_run.status.success(), "{}", stderr);
+    assert!(stderr.contains("was already generated"), "{}", stderr);
+    assert!(!stderr.contains("Dry run"), "{}", stderr);
+    assert_eq!(first.stdout, dry_run.stdout);
+
+    // A different key advances the chain.
+    let third = next(
+        &chain,
+        &["--comment", "Build 1234", "--idempotency-key", "build-1235"],
+    );
+    assert!(third.status.success());
+    assert!(chain.join("marks/mark-2.json").exists());
+}
+
+#[test]
+fn test_repeated_key_with_a_different_comment_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+    let first = next(
+        &chain,
+        &["--comment", "Build 1234", "--idempotency-key", "build-1234"],
+    );
+    assert!(first.status.success());
+
+    let second = next(
+        &chain,
+        &["--comment", "Build 9999", "--idempotency-key", "build-1234"],
+    );
+    assert!(!second.status.success());
+    let stderr = String::from_utf8_lossy(&second.stderr);
+    assert!(
+        stderr.contains("was used for mark 1 with a different comment"),
+        "{}",
+        stderr
+    );
+    assert!(!chain.join("marks/mark-2.json").exists());
+}
+
+#[test]
+fn test_undo_forgets_the_key() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 0);
+    let args = ["--idempotency-key", "build-1234", "--format", "ur"];
+    assert!(next(&chain, &args).status.success());
+
+    provenance()
+        .arg("undo")
+        .arg(&chain)
+        .arg("--force")
+        .assert()
+        .success();
+
+    let output = next(&chain, &args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stderr.contains("Mark 1 written to:"), "{}", stderr);
+}
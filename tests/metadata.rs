@@ -0,0 +1,64 @@
+mod common;
+
+use common::{create_chain_with, provenance, run};
+use tempfile::TempDir;
+
+#[test]
+fn test_metadata_is_shown() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain_with(
+        &chain,
+        &[
+            "--name",
+            "Releases",
+            "--owner",
+            "Example Corp",
+            "--url",
+            "https://example.com/releases",
+        ],
+        1,
+    );
+
+    let status = run(provenance().args(["status"]).arg(&chain));
+    assert!(status.contains("Name: Releases"), "{}", status);
+    assert!(status.contains("Owner: Example Corp"), "{}", status);
+    assert!(
+        status.contains("URL: https://example.com/releases"),
+        "{}",
+        status
+    );
+    assert!(!status.contains("Description:"), "{}", status);
+
+    let markdown = run(provenance().args(["print", "--end", "0"]).arg(&chain));
+    assert!(markdown.contains("- Name: Releases"), "{}", markdown);
+
+    let json =
+        run(provenance().args(["print", "--format", "json"]).arg(&chain));
+    let marks: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(marks[0]["metadata"]["name"], "Releases");
+    assert_eq!(marks[0]["metadata"]["description"], serde_json::Value::Null);
+    assert!(marks[1].get("metadata").is_none());
+
+    let report = run(provenance().args(["validate", "--dir"]).arg(&chain));
+    assert!(report.contains("Chain names:"), "{}", report);
+    assert!(report.contains(": Releases"), "{}", report);
+
+    let report = run(provenance()
+        .args(["validate", "--format", "json-pretty", "--dir"])
+        .arg(&chain));
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    let metadata = report["chainMetadata"].as_object().unwrap();
+    assert_eq!(metadata.values().next().unwrap()["owner"], "Example Corp");
+}
+
+#[test]
+fn test_metadata_conflicts_with_info() {
+    let temp_dir = TempDir::new().unwrap();
+    provenance()
+        .arg("new")
+        .arg(temp_dir.path().join("chain"))
+        .args(["--name", "Releases", "--info", "0x01", "--quiet"])
+        .assert()
+        .failure();
+}
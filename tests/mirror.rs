@@ -0,0 +1,98 @@
+mod common;
+
+use common::{create_chain, mark_urs, provenance};
+use tempfile::TempDir;
+
+/// Create a chain and a mirror of it holding the chain's genesis mark.
+fn create_mirror(last: u32) -> (TempDir, Vec<String>) {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, last);
+    let urs = mark_urs(&chain);
+    provenance()
+        .args(["mirror", "init"])
+        .arg(temp_dir.path().join("mirror"))
+        .args(["--genesis", &urs[0]])
+        .assert()
+        .success();
+    (temp_dir, urs)
+}
+
+#[test]
+fn test_mirror_add_and_inspect() {
+    let (temp_dir, urs) = create_mirror(3);
+    let mirror = temp_dir.path().join("mirror");
+    assert!(!mirror.join("generator.json").exists());
+
+    provenance()
+        .args(["mirror", "add"])
+        .arg(&mirror)
+        .args(&urs[1..])
+        .assert()
+        .success();
+
+    // The mirror holds the same marks as the chain.
+    assert_eq!(mark_urs(&mirror), urs);
+
+    provenance()
+        .arg("validate")
+        .arg("--dir")
+        .arg(&mirror)
+        .assert()
+        .success();
+
+    let output = provenance().arg("status").arg(&mirror).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("Kind: mirror"), "{}", stdout);
+    assert!(stdout.contains("Marks: 4"), "{}", stdout);
+    assert!(stdout.contains("Head: mark 3"), "{}", stdout);
+}
+
+#[test]
+fn test_mirror_rejects_marks_that_do_not_extend_head() {
+    let (temp_dir, urs) = create_mirror(3);
+    let mirror = temp_dir.path().join("mirror");
+
+    // Mark 2 skips mark 1, so nothing is added, not even the valid mark 1
+    // given before it.
+    provenance()
+        .args(["mirror", "add"])
+        .arg(&mirror)
+        .args([&urs[1], &urs[3]])
+        .assert()
+        .failure();
+    assert!(!mirror.join("marks/mark-1.json").exists());
+
+    // A mark from another chain.
+    let other = temp_dir.path().join("other");
+    create_chain(&other, 1);
+    provenance()
+        .args(["mirror", "add"])
+        .arg(&mirror)
+        .arg(&mark_urs(&other)[1])
+        .assert()
+        .failure();
+
+    // A chain with a generator is not a mirror.
+    provenance()
+        .args(["mirror", "add"])
+        .arg(&other)
+        .arg(&urs[1])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_mirror_init_requires_genesis() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 1);
+    let urs = mark_urs(&chain);
+    provenance()
+        .args(["mirror", "init"])
+        .arg(temp_dir.path().join("mirror"))
+        .args(["--genesis", &urs[1]])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{create_chain, provenance_with_config, run};
+use tempfile::TempDir;
+
+fn create_notice(config_home: &Path, args: &[&str]) -> String {
+    let notice = run(provenance_with_config(config_home)
+        .args(["notice", "create"])
+        .args(args));
+    assert!(notice.starts_with("ur:envelope/"), "{}", notice);
+    notice.trim().to_string()
+}
+
+#[test]
+fn test_notice_distrusts_later_marks() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 3);
+
+    let notice = create_notice(
+        &config_home,
+        &[
+            chain.to_str().unwrap(),
+            "--last-trusted-seq",
+            "1",
+            "--reason",
+            "Laptop stolen",
+        ],
+    );
+
+    // The notice has no effect until it is in the trust store.
+    provenance_with_config(&config_home)
+        .args(["validate", "--dir"])
+        .arg(&chain)
+        .assert()
+        .success();
+
+    let output = provenance_with_config(&config_home)
+        .args(["notice", "add", &notice])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(
+        stdout.contains("marks after mark 1 are untrusted"),
+        "{}",
+        stdout
+    );
+    let output = provenance_with_config(&config_home)
+        .args(["notice", "add", &notice])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("already holds"));
+
+    for stream in [false, true] {
+        let mut cmd = provenance_with_config(&config_home);
+        cmd.args(["validate", "--dir"]).arg(&chain);
+        if stream {
+            cmd.arg("--stream");
+        }
+        let output = cmd.output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let all = format!("{}{}", stdout, stderr);
+        assert_eq!(output.status.code(), Some(16), "{}", all);
+        assert!(all.contains("mark 2: a compromise notice"), "{}", all);
+        assert!(all.contains("mark 3: a compromise notice"), "{}", all);
+        assert!(!all.contains("mark 1: a compromise notice"), "{}", all);
+        assert!(all.contains("(Laptop stolen)"), "{}", all);
+    }
+}
+
+#[test]
+fn test_notice_by_chain_id_and_date() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 3);
+
+    let output = provenance_with_config(&config_home)
+        .args(["status", "--format", "json"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    let status: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+    let chain_id = status["chain"].as_str().unwrap().to_string();
+
+    let notice = create_notice(
+        &config_home,
+        &[
+            "--chain-id",
+            &chain_id,
+            "--last-trusted-date",
+            "2023-06-22T00:00:00Z",
+        ],
+    );
+    provenance_with_config(&config_home)
+        .args(["notice", "add"])
+        .write_stdin(notice)
+        .assert()
+        .success();
+
+    let output = provenance_with_config(&config_home)
+        .args(["validate", "--format", "json-compact", "--dir"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(16));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("marks dated after 2023-06-22"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("\"seq\":2"), "{}", stderr);
+    assert!(!stderr.contains("\"seq\":1,"), "{}", stderr);
+}
+
+#[test]
+fn test_unreadable_notice_is_skipped_with_a_warning() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_home = temp_dir.path().join("config");
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 3);
+    let notices = config_home.join("provenance/notices");
+    fs::create_dir_all(&notices).unwrap();
+    fs::write(notices.join("broken.ur"), "not a notice").unwrap();
+
+    let output = provenance_with_config(&config_home)
+        .args(["validate", "--dir"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(
+        stderr.contains("Warning: skipped compromise notice"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("broken.ur"), "{}", stderr);
+}
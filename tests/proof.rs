@@ -0,0 +1,102 @@
+mod common;
+
+use std::path::Path;
+
+use common::{create_chain, mark_urs, provenance, run};
+use tempfile::TempDir;
+
+fn proof(path: &Path, args: &[&str]) -> String {
+    run(provenance().arg("proof").arg(path).args(args))
+        .trim()
+        .to_string()
+}
+
+#[test]
+fn test_full_proof_verifies_against_genesis() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 4);
+
+    let bundle = proof(&chain, &[]);
+    assert!(bundle.starts_with("ur:envelope/"), "{}", bundle);
+
+    let output = provenance()
+        .arg("verify-proof")
+        .arg(&bundle)
+        .args(["--genesis", &mark_urs(&chain)[0]])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(
+        stdout.contains("Proof verified: marks 0 through 4"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_partial_proof_verifies_against_preceding_mark() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 5);
+
+    // The bundle is read from stdin when it is not given as an argument.
+    let bundle = proof(&chain, &["--from", "3", "--to", "5"]);
+    let output = provenance()
+        .arg("verify-proof")
+        .args(["--genesis", &mark_urs(&chain)[2]])
+        .write_stdin(bundle)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(
+        stdout.contains("marks 3 through 5") && stdout.contains("from mark 2"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_proof_fails_for_wrong_anchor() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    let other = temp_dir.path().join("other");
+    create_chain(&chain, 3);
+    create_chain(&other, 0);
+
+    // A gap between the anchor and the bundle's first mark.
+    let bundle = proof(&chain, &["--from", "2"]);
+    provenance()
+        .arg("verify-proof")
+        .arg(&bundle)
+        .args(["--genesis", &mark_urs(&chain)[0]])
+        .assert()
+        .failure();
+
+    // An anchor from another chain.
+    let bundle = proof(&chain, &[]);
+    provenance()
+        .arg("verify-proof")
+        .arg(&bundle)
+        .args(["--genesis", &mark_urs(&other)[0]])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_proof_rejects_bad_range() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 2);
+
+    for args in [["--from", "2", "--to", "1"], ["--from", "0", "--to", "3"]] {
+        provenance()
+            .arg("proof")
+            .arg(&chain)
+            .args(args)
+            .assert()
+            .failure();
+    }
+}
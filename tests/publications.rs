@@ -0,0 +1,134 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{create_chain, provenance, provenance_with_config, run};
+use tempfile::TempDir;
+
+fn publish(chain: &Path, seq: &str, url: &str) -> String {
+    run(provenance()
+        .arg("publish-record")
+        .arg(chain)
+        .args([seq, "--url", url])
+        .args(["--at", "2023-06-25T09:00:00Z"]))
+}
+
+#[test]
+fn test_publish_record_and_unpublished_status() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 2);
+
+    let output = provenance()
+        .args(["status", "--unpublished"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Unpublished marks: 0, 1, 2"), "{}", stdout);
+
+    let stdout = publish(&chain, "0", "https://example.com/genesis");
+    assert!(
+        stdout.contains("Mark 0 recorded as published"),
+        "{}",
+        stdout
+    );
+    publish(&chain, "1", "https://example.com/one");
+    publish(&chain, "1", "https://mirror.example.org/one");
+    let stdout = publish(&chain, "1", "https://example.com/one");
+    assert!(stdout.contains("already recorded"), "{}", stdout);
+
+    let output = provenance()
+        .args(["status", "--unpublished", "--format", "json"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    let status: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(status["unpublished"], serde_json::json!([2]));
+
+    let output = provenance()
+        .args(["print", "--format", "json"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    let marks: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+    let published = marks[1]["publications"].as_array().unwrap();
+    assert_eq!(published.len(), 2);
+    assert_eq!(published[0]["url"], "https://example.com/one");
+    assert!(
+        published[0]["at"]
+            .as_str()
+            .unwrap()
+            .starts_with("2023-06-25"),
+        "{}",
+        published[0]
+    );
+    assert!(marks[2].get("publications").is_none());
+}
+
+#[test]
+fn test_publish_record_needs_an_existing_mark() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 2);
+
+    provenance()
+        .arg("publish-record")
+        .arg(&chain)
+        .args(["7", "--url", "https://example.com/seven"])
+        .assert()
+        .failure();
+    assert!(!chain.join("publications.json").exists());
+}
+
+#[test]
+fn test_publish_record_again_adds_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 2);
+
+    for _ in 0..2 {
+        provenance()
+            .arg("publish-record")
+            .arg(&chain)
+            .args(["1", "--url", "https://example.com/one"])
+            .assert()
+            .success();
+    }
+    let records: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(chain.join("publications.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(records.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_publish_record_of_a_named_chain() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 1);
+    let config_home = temp_dir.path().join("config");
+    fs::create_dir_all(config_home.join("provenance")).unwrap();
+    fs::write(
+        config_home.join("provenance/config.toml"),
+        format!("[chains]\nreleases = {:?}\n", chain),
+    )
+    .unwrap();
+
+    // The sequence number stands alone when no path precedes it.
+    let stdout = run(provenance_with_config(&config_home)
+        .env("PROVENANCE_CHAIN", "releases")
+        .args(["publish-record", "1", "--url", "https://example.com/one"]));
+    assert!(stdout.contains("Mark 1 recorded"), "{}", stdout);
+    let stdout = run(provenance_with_config(&config_home).args([
+        "publish-record",
+        "--chain",
+        "releases",
+        "0",
+        "--url",
+        "https://example.com/zero",
+    ]));
+    assert!(stdout.contains("Mark 0 recorded"), "{}", stdout);
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{create_chain, provenance};
+use tempfile::TempDir;
+
+fn succeed(old: &Path, new: &Path) {
+    provenance()
+        .arg("new")
+        .arg(new)
+        .arg("--succeeds")
+        .arg(old)
+        .args(["--name", "Successor"])
+        .args(["--date", "2023-06-22T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_succeeds_closes_the_old_chain() {
+    let temp_dir = TempDir::new().unwrap();
+    let old = temp_dir.path().join("old");
+    let new = temp_dir.path().join("new");
+    create_chain(&old, 1);
+    succeed(&old, &new);
+
+    // The old chain is closed by a mark that follows its head.
+    assert!(old.join("marks/mark-2.json").is_file());
+    provenance()
+        .arg("next")
+        .arg(&old)
+        .arg("--quiet")
+        .assert()
+        .failure();
+    let config = fs::read_to_string(new.join("chain.toml")).unwrap();
+    assert!(config.contains("predecessor"), "{}", config);
+
+    // Validating the new chain follows the link back to the old one.
+    let output = provenance()
+        .args(["validate", "--dir"])
+        .arg(&new)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}{}", stdout, stderr);
+    assert!(stdout.contains("History:"), "{}", stdout);
+    assert!(stdout.contains("1. "), "{}", stdout);
+    assert!(stdout.contains(": marks 0 through 2"), "{}", stdout);
+    assert!(stdout.contains(": marks 0 through 0"), "{}", stdout);
+
+    let output = provenance()
+        .args(["validate", "--format", "json-pretty", "--dir"])
+        .arg(&new)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).unwrap();
+    let history = json["history"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["last"], 2);
+    assert_eq!(history[1]["first"], 0);
+    assert_eq!(json["chainMetadata"].as_object().unwrap().len(), 1);
+
+    // A chain that has been succeeded cannot be succeeded again.
+    provenance()
+        .arg("new")
+        .arg(temp_dir.path().join("another"))
+        .arg("--succeeds")
+        .arg(&old)
+        .assert()
+        .failure();
+    assert!(!temp_dir.path().join("another").exists());
+}
+
+#[test]
+fn test_validate_reports_broken_succession() {
+    let temp_dir = TempDir::new().unwrap();
+    let old = temp_dir.path().join("old");
+    let new = temp_dir.path().join("new");
+    create_chain(&old, 1);
+    succeed(&old, &new);
+
+    // Without the closing mark, the old chain does not name its successor.
+    fs::remove_file(old.join("marks/mark-2.json")).unwrap();
+
+    let output = provenance()
+        .args(["validate", "--dir"])
+        .arg(&new)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(15), "{}{}", stdout, stderr);
+    assert!(
+        stderr.contains("was not closed in favor of this chain"),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("History:"), "{}", stderr);
+}
+
+#[test]
+fn test_succeeds_refuses_a_mirror() {
+    let temp_dir = TempDir::new().unwrap();
+    let old = temp_dir.path().join("old");
+    let new = temp_dir.path().join("new");
+    create_chain(&old, 1);
+    fs::remove_file(old.join("generator.json")).unwrap();
+
+    let output = provenance()
+        .arg("new")
+        .arg(&new)
+        .arg("--succeeds")
+        .arg(&old)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Cannot succeed a mirror"), "{}", stderr);
+    assert!(!new.exists());
+}
+
+#[test]
+fn test_succeeds_with_relative_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    create_chain(&temp_dir.path().join("old-chain"), 1);
+    provenance()
+        .current_dir(temp_dir.path())
+        .args(["new", "new-chain", "--succeeds", "old-chain"])
+        .args(["--date", "2023-06-22T12:00:00Z", "--quiet"])
+        .assert()
+        .success();
+
+    // The link holds from the chain's own parent and from anywhere else.
+    for (cwd, dir) in [
+        (
+            temp_dir.path().to_path_buf(),
+            Path::new("new-chain").to_path_buf(),
+        ),
+        (std::env::temp_dir(), temp_dir.path().join("new-chain")),
+    ] {
+        let output = provenance()
+            .current_dir(cwd)
+            .args(["validate", "--dir"])
+            .arg(dir)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{}{}", stdout, stderr);
+        assert!(stdout.contains("History:"), "{}", stdout);
+    }
+}
+
+#[test]
+fn test_link_survives_moving_both_chains() {
+    let temp_dir = TempDir::new().unwrap();
+    let chains = temp_dir.path().join("chains");
+    fs::create_dir(&chains).unwrap();
+    create_chain(&chains.join("old"), 1);
+    succeed(&chains.join("old"), &chains.join("new"));
+    let config = fs::read_to_string(chains.join("new/chain.toml")).unwrap();
+    assert!(config.contains("predecessor = \"../old\""), "{}", config);
+
+    let moved = temp_dir.path().join("moved");
+    fs::rename(&chains, &moved).unwrap();
+    let output = provenance()
+        .args(["validate", "--dir"])
+        .arg(moved.join("new"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}{}", stdout, stderr);
+    assert!(stdout.contains("History:"), "{}", stdout);
+}
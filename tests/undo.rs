@@ -0,0 +1,161 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{create_chain, provenance};
+use tempfile::TempDir;
+
+fn next(chain: &Path, day: u32) -> String {
+    let output = common::next(
+        chain,
+        &[
+            "--date",
+            &format!("2023-06-{}T12:00:00Z", day),
+            "--format",
+            "ur",
+            "--quiet",
+        ],
+    );
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_undo_restores_the_generator() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 1);
+    let generator = fs::read_to_string(chain.join("generator.json")).unwrap();
+    let mark = next(&chain, 22);
+    assert!(chain.join("generator-history/before-mark-2.json").is_file());
+
+    // Declining the prompt leaves the chain unchanged.
+    provenance()
+        .arg("undo")
+        .arg(&chain)
+        .write_stdin("n\n")
+        .assert()
+        .failure();
+    assert!(chain.join("marks/mark-2.json").is_file());
+
+    let output = provenance()
+        .arg("undo")
+        .arg(&chain)
+        .write_stdin("y\n")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("Mark 2 removed"), "{}", stdout);
+    assert!(!chain.join("marks/mark-2.json").exists());
+    assert_eq!(
+        fs::read_to_string(chain.join("generator.json")).unwrap(),
+        generator
+    );
+
+    // The same mark can be generated again.
+    assert_eq!(next(&chain, 22), mark);
+    provenance()
+        .args(["validate", "--dir"])
+        .arg(&chain)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_undo_refuses_published_and_genesis_marks() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 1);
+    provenance()
+        .args(["undo", "--force"])
+        .arg(&chain)
+        .assert()
+        .success();
+    let output = provenance()
+        .args(["undo", "--force"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("genesis mark"), "{}", stderr);
+    assert!(chain.join("marks/mark-0.json").is_file());
+
+    next(&chain, 21);
+    provenance()
+        .arg("publish-record")
+        .arg(&chain)
+        .args(["1", "--url", "https://example.com/marks"])
+        .assert()
+        .success();
+    let output = provenance()
+        .args(["undo", "--force"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("has been published"), "{}", stderr);
+    assert!(chain.join("marks/mark-1.json").is_file());
+}
+
+#[test]
+fn test_undo_reads_records_without_url() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 1);
+    fs::write(chain.join("publications.json"), r#"[{"seq": 1}]"#).unwrap();
+
+    let output = provenance()
+        .args(["undo", "--force"])
+        .arg(&chain)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("has been published"), "{}", stderr);
+}
+
+#[test]
+fn test_snapshots_that_cannot_be_used_are_removed() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain = temp_dir.path().join("chain");
+    create_chain(&chain, 10);
+    let snapshots = || {
+        let mut seqs: Vec<u32> = fs::read_dir(chain.join("generator-history"))
+            .unwrap()
+            .map(|entry| {
+                let name = entry.unwrap().file_name();
+                let name = name.to_str().unwrap();
+                name["before-mark-".len()..name.len() - ".json".len()]
+                    .parse()
+                    .unwrap()
+            })
+            .collect();
+        seqs.sort();
+        seqs
+    };
+    assert_eq!(snapshots(), (1..=10).collect::<Vec<_>>());
+
+    // Only the most recent marks can be undone.
+    common::next(&chain, &["--date", "2023-07-01T12:00:00Z", "--quiet"]);
+    assert_eq!(snapshots(), (2..=11).collect::<Vec<_>>());
+
+    // Nor can a published mark, or any before it.
+    provenance()
+        .arg("publish-record")
+        .arg(&chain)
+        .args(["5", "--url", "https://example.com/5"])
+        .assert()
+        .success();
+    assert_eq!(snapshots(), (6..=11).collect::<Vec<_>>());
+
+    provenance()
+        .arg("close")
+        .arg(&chain)
+        .args(["--reason", "Retired", "--date", "2023-07-02T12:00:00Z"])
+        .assert()
+        .success();
+    assert!(snapshots().is_empty());
+}
@@ -1,9 +1,9 @@
-use std::process::Command;
+mod common;
 
-use assert_cmd::cargo::cargo_bin_cmd;
 use bc_envelope::prelude::*;
 use bc_ur::{UR, UREncodable};
 use chrono::TimeZone;
+use common::provenance;
 use dcbor::prelude::CBORTaggedEncodable;
 use indoc::indoc;
 use known_values::PROVENANCE;
@@ -81,7 +81,7 @@ fn wrapped_mark_ur(
 }
 
 fn run_validate_command(ur_strings: &[String], warn: bool) -> (bool, String) {
-    let mut cmd = Command::new(env!("CARGO_BIN_EXE_provenance"));
+    let mut cmd = provenance();
     cmd.arg("validate");
 
     if warn {
@@ -366,7 +366,7 @@ mod quartile_directory_workflow {
 
         // Step 1: Create a new chain with Quartile resolution using a fixed
         // date
-        let new_output = cargo_bin_cmd!("provenance")
+        let new_output = provenance()
             .arg("new")
             .arg(&chain_path)
             .arg("--resolution")
@@ -391,7 +391,7 @@ mod quartile_directory_workflow {
         // Step 2: Generate three additional marks using the 'next' subcommand
         // with sequential dates
         for i in 1..=3 {
-            cargo_bin_cmd!("provenance")
+            provenance()
                 .arg("next")
                 .arg(&chain_path)
                 .arg("--date")
@@ -404,7 +404,7 @@ mod quartile_directory_workflow {
         }
 
         // Step 3: Validate all marks in the directory using 'validate --dir'
-        let validate_output = cargo_bin_cmd!("provenance")
+        let validate_output = provenance()
             .arg("validate")
             .arg("--dir")
             .arg(&chain_path)
@@ -547,3 +547,849 @@ mod quartile_directory_workflow {
         );
     }
 }
+
+fn run_validate_with_args(
+    args: &[&str],
+    ur_strings: &[String],
+) -> (Option<i32>, String) {
+    let output = provenance()
+        .arg("validate")
+        .args(args)
+        .args(ur_strings)
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    (output.status.code(), format!("{}{}", stdout, stderr))
+}
+
+mod exit_codes {
+    use super::*;
+
+    fn marks_with_gap() -> Vec<String> {
+        let marks = create_test_marks(4, ProvenanceMarkResolution::Low, "test");
+        marks_to_ur_strings(&[
+            marks[0].clone(),
+            marks[1].clone(),
+            marks[3].clone(),
+        ])
+    }
+
+    #[test]
+    fn test_gap_exit_code() {
+        let (code, output) = run_validate_with_args(&[], &marks_with_gap());
+        assert_eq!(code, Some(5), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_missing_genesis_exit_code() {
+        let marks = create_test_marks(3, ProvenanceMarkResolution::Low, "test");
+        let ur_strings = marks_to_ur_strings(&marks[1..]);
+
+        let (code, output) = run_validate_with_args(&[], &ur_strings);
+        assert_eq!(code, Some(6), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_multiple_chains_exit_code() {
+        let mut marks =
+            create_test_marks(2, ProvenanceMarkResolution::Low, "alice");
+        marks.extend(create_test_marks(
+            2,
+            ProvenanceMarkResolution::Low,
+            "bob",
+        ));
+
+        let (code, output) =
+            run_validate_with_args(&[], &marks_to_ur_strings(&marks));
+        assert_eq!(code, Some(4), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_parse_error_exit_code() {
+        let (code, output) =
+            run_validate_with_args(&[], &["ur:invalid/abcd".to_string()]);
+        assert_eq!(code, Some(3), "Output: {}", output);
+        assert!(output.contains("Failed to parse UR"), "Output: {}", output);
+    }
+
+    fn forked_marks() -> Vec<String> {
+        provenance_mark::register_tags();
+        let mut generator = ProvenanceMarkGenerator::new_with_passphrase(
+            ProvenanceMarkResolution::Low,
+            "test",
+        );
+        let date = Date::from_string("2023-06-20").unwrap();
+        let mark0 = generator.next(date, None::<String>);
+        let mark1 = generator.next(date, None::<String>);
+        let mut fork = generator.clone();
+        let mark2 = generator.next(date, None::<String>);
+        let mark2_fork =
+            fork.next(Date::from_string("2023-06-21").unwrap(), None::<String>);
+
+        marks_to_ur_strings(&[mark0, mark1, mark2, mark2_fork])
+    }
+
+    #[test]
+    fn test_fork_exit_code() {
+        let (code, output) = run_validate_with_args(&[], &forked_marks());
+        assert_eq!(code, Some(9), "Output: {}", output);
+        assert!(output.contains("fork:"), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_fork_outranks_stale() {
+        let (code, output) = run_validate_with_args(
+            &["--max-age", "30d", "--now", "2030-01-01"],
+            &forked_marks(),
+        );
+        assert_eq!(code, Some(9), "Output: {}", output);
+        assert!(output.contains("stale:"), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_allow_class() {
+        let (code, output) =
+            run_validate_with_args(&["--allow", "gap"], &marks_with_gap());
+        assert_eq!(code, Some(0), "Output: {}", output);
+        assert!(output.contains("gap: 2 missing"), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_fail_on_other_class() {
+        let (code, output) = run_validate_with_args(
+            &["--fail-on", "fork,mismatch"],
+            &marks_with_gap(),
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_fail_on_matching_class() {
+        let (code, output) = run_validate_with_args(
+            &["--fail-on", "gap,fork"],
+            &marks_with_gap(),
+        );
+        assert_eq!(code, Some(5), "Output: {}", output);
+    }
+}
+
+mod policy {
+    use super::*;
+
+    fn write_policy(dir: &TempDir, policy: &str) -> String {
+        let path = dir.path().join("policy.json");
+        std::fs::write(&path, policy).expect("write policy");
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_policy_satisfied() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let policy = write_policy(
+            &temp_dir,
+            r#"{ "requireGenesis": true, "maxInterval": "2d", "minResolution": "low" }"#,
+        );
+        let marks = create_test_marks(4, ProvenanceMarkResolution::Low, "test");
+
+        let (code, output) = run_validate_with_args(
+            &["--policy", &policy],
+            &marks_to_ur_strings(&marks),
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+        assert!(output.trim().is_empty(), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_policy_violations_in_text() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let policy = write_policy(
+            &temp_dir,
+            r#"{ "requireGenesis": true, "maxInterval": "12h", "minResolution": "quartile" }"#,
+        );
+        let marks = create_test_marks(3, ProvenanceMarkResolution::Low, "test");
+
+        let (code, output) = run_validate_with_args(
+            &["--policy", &policy, "--allow", "missing-genesis"],
+            &marks_to_ur_strings(&marks[1..]),
+        );
+        assert_eq!(code, Some(10), "Output: {}", output);
+        assert!(
+            output.contains("policy (requireGenesis)"),
+            "Output: {}",
+            output
+        );
+        assert!(
+            output.contains("policy (maxInterval)"),
+            "Output: {}",
+            output
+        );
+        assert!(
+            output.contains("resolution low is below the required quartile"),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_policy_violations_in_json() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let policy =
+            write_policy(&temp_dir, r#"{ "requireInfoDigest": true }"#);
+        let marks = create_test_marks(2, ProvenanceMarkResolution::Low, "test");
+
+        let (code, output) = run_validate_with_args(
+            &["--policy", &policy, "--format", "json-compact", "--warn"],
+            &marks_to_ur_strings(&marks),
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+        let json: serde_json::Value =
+            serde_json::from_str(output.trim()).expect("JSON output");
+        let rules: Vec<&str> = json["issues"]
+            .as_array()
+            .expect("issues array")
+            .iter()
+            .filter_map(|issue| issue["rule"].as_str())
+            .collect();
+        assert_eq!(rules, vec!["requireInfoDigest", "requireInfoDigest"]);
+    }
+
+    #[test]
+    fn test_invalid_policy_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let policy = write_policy(&temp_dir, r#"{ "maxGap": 3 }"#);
+        let marks = create_test_marks(1, ProvenanceMarkResolution::Low, "test");
+
+        let (code, output) = run_validate_with_args(
+            &["--policy", &policy],
+            &marks_to_ur_strings(&marks),
+        );
+        assert_eq!(code, Some(1), "Output: {}", output);
+        assert!(
+            output.contains("Failed to parse policy"),
+            "Output: {}",
+            output
+        );
+    }
+}
+
+mod report_formats {
+    use super::*;
+
+    fn write_document(dir: &TempDir, ur_strings: &[String]) -> String {
+        let mut document = String::from("# Published marks\n\n");
+        for (seq, ur) in ur_strings.iter().enumerate() {
+            document.push_str(&format!("- Mark {}: `{}`\n", seq, ur));
+        }
+        let path = dir.path().join("marks.md");
+        std::fs::write(&path, document).expect("write document");
+        path.to_string_lossy().to_string()
+    }
+
+    fn marks_with_gap() -> Vec<String> {
+        let marks = create_test_marks(4, ProvenanceMarkResolution::Low, "test");
+        marks_to_ur_strings(&[
+            marks[0].clone(),
+            marks[1].clone(),
+            marks[3].clone(),
+        ])
+    }
+
+    #[test]
+    fn test_scan_document() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let marks = create_test_marks(3, ProvenanceMarkResolution::Low, "test");
+        let document = write_document(&temp_dir, &marks_to_ur_strings(&marks));
+
+        let (code, output) =
+            run_validate_with_args(&["--file", &document], &[]);
+        assert_eq!(code, Some(0), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_junit_output() {
+        let (code, output) =
+            run_validate_with_args(&["--format", "junit"], &marks_with_gap());
+        assert_eq!(code, Some(5), "Output: {}", output);
+        assert!(
+            output.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#),
+            "Output: {}",
+            output
+        );
+        assert!(
+            output.contains(r#"tests="4" failures="1""#),
+            "Output: {}",
+            output
+        );
+        assert!(
+            output.contains(r#"<failure type="gap""#),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_junit_allowed_issue_is_not_a_failure() {
+        let (code, output) = run_validate_with_args(
+            &["--format", "junit", "--allow", "gap"],
+            &marks_with_gap(),
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+        assert!(output.contains(r#"failures="0""#), "Output: {}", output);
+        assert!(output.contains("<system-out>gap: "), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_sarif_output_locates_issue_in_document() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let document = write_document(&temp_dir, &marks_with_gap());
+
+        let output = provenance()
+            .args(["validate", "--format", "sarif", "--file", &document])
+            .output()
+            .expect("Failed to execute command");
+        assert_eq!(output.status.code(), Some(5));
+        let sarif: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("SARIF is JSON");
+        assert_eq!(sarif["version"], "2.1.0");
+
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1, "SARIF: {}", sarif);
+        assert_eq!(results[0]["ruleId"], "gap");
+        assert_eq!(results[0]["level"], "error");
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["region"]["startLine"], 5, "SARIF: {}", sarif);
+    }
+}
+
+mod explain {
+    use super::*;
+
+    #[test]
+    fn test_explain_gap() {
+        let marks = create_test_marks(5, ProvenanceMarkResolution::Low, "test");
+        let ur_strings = marks_to_ur_strings(&[
+            marks[0].clone(),
+            marks[1].clone(),
+            marks[4].clone(),
+        ]);
+
+        let (code, output) =
+            run_validate_with_args(&["--explain"], &ur_strings);
+        assert_eq!(code, Some(5), "Output: {}", output);
+        assert!(output.contains("Explanations:"), "Output: {}", output);
+        assert!(
+            output.contains("Marks 2 through 3 are missing."),
+            "Output: {}",
+            output
+        );
+        assert!(output.contains("--start 2 --end 3"), "Output: {}", output);
+        assert!(
+            output.contains(&marks[4].bytewords_identifier(true)),
+            "Output: {}",
+            output
+        );
+        assert!(output.contains("exit code 5"), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_explain_allowed_issue_in_json() {
+        let marks = create_test_marks(3, ProvenanceMarkResolution::Low, "test");
+        let ur_strings = marks_to_ur_strings(&marks[1..]);
+
+        let output = provenance()
+            .args(["validate", "--explain", "--format", "json-compact"])
+            .args(["--allow", "missing-genesis"])
+            .args(&ur_strings)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("output is JSON");
+        let explanation = json["issues"][0]["explanation"].as_str().unwrap();
+        assert!(
+            explanation.contains("--start 0 --end 0"),
+            "Explanation: {}",
+            explanation
+        );
+        assert!(
+            explanation.contains("does not fail validation"),
+            "Explanation: {}",
+            explanation
+        );
+    }
+
+    #[test]
+    fn test_no_explanations_for_valid_chain() {
+        let marks = create_test_marks(3, ProvenanceMarkResolution::Low, "test");
+        let (code, output) = run_validate_with_args(
+            &["--explain"],
+            &marks_to_ur_strings(&marks),
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+        assert!(output.trim().is_empty(), "Output: {}", output);
+    }
+}
+
+mod freshness {
+    use super::*;
+
+    fn three_marks() -> Vec<String> {
+        let marks = create_test_marks(3, ProvenanceMarkResolution::Low, "test");
+        marks_to_ur_strings(&marks)
+    }
+
+    #[test]
+    fn test_fresh_chain() {
+        let (code, output) = run_validate_with_args(
+            &["--max-age", "30d", "--now", "2023-07-01"],
+            &three_marks(),
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+        assert!(output.trim().is_empty(), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_stale_chain() {
+        let (code, output) = run_validate_with_args(
+            &["--max-age", "30d", "--now", "2023-08-01"],
+            &three_marks(),
+        );
+        assert_eq!(code, Some(11), "Output: {}", output);
+        assert!(
+            output.contains("stale: chain ") && output.contains(", mark 2: "),
+            "Output: {}",
+            output
+        );
+        assert!(output.contains("older than 30d"), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_stale_chain_measured_against_current_date() {
+        let (code, output) =
+            run_validate_with_args(&["--max-age", "1w"], &three_marks());
+        assert_eq!(code, Some(11), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_stale_chain_as_warning() {
+        let (code, output) = run_validate_with_args(
+            &["--max-age", "1w", "--allow", "stale"],
+            &three_marks(),
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+        assert!(output.contains("stale: "), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_silence_between_marks() {
+        let marks = create_test_marks(4, ProvenanceMarkResolution::Low, "test");
+        let ur_strings = marks_to_ur_strings(&[
+            marks[0].clone(),
+            marks[1].clone(),
+            marks[3].clone(),
+        ]);
+
+        let (code, output) = run_validate_with_args(
+            &["--max-interval", "2d", "--allow", "gap"],
+            &ur_strings,
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+
+        let (code, output) = run_validate_with_args(
+            &["--max-interval", "1d", "--allow", "gap"],
+            &ur_strings,
+        );
+        assert_eq!(code, Some(12), "Output: {}", output);
+        assert!(
+            output.contains("2d since mark 1 exceeds 1d"),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_invalid_duration() {
+        let (code, output) =
+            run_validate_with_args(&["--max-age", "soon"], &three_marks());
+        assert_eq!(code, Some(2), "Output: {}", output);
+    }
+}
+
+mod directory_checks {
+    use super::*;
+
+    /// Create a chain directory holding marks 0 through `last`.
+    fn create_chain(temp_dir: &TempDir, last: u32) -> std::path::PathBuf {
+        let chain_path = temp_dir.path().join("chain");
+        provenance()
+            .arg("new")
+            .arg(&chain_path)
+            .args(["--date", "2023-06-20T12:00:00Z", "--quiet"])
+            .assert()
+            .success();
+        for i in 1..=last {
+            provenance()
+                .arg("next")
+                .arg(&chain_path)
+                .args(["--date", &format!("2023-07-{:02}T12:00:00Z", i)])
+                .arg("--quiet")
+                .assert()
+                .success();
+        }
+        chain_path
+    }
+
+    fn validate_dir(chain_path: &std::path::Path) -> (Option<i32>, String) {
+        run_validate_with_args(&["--dir", &chain_path.to_string_lossy()], &[])
+    }
+
+    fn mark_path(chain_path: &std::path::Path, seq: u32) -> std::path::PathBuf {
+        chain_path.join("marks").join(format!("mark-{}.json", seq))
+    }
+
+    #[test]
+    fn test_more_than_ten_marks() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let chain_path = create_chain(&temp_dir, 11);
+
+        let (code, output) = validate_dir(&chain_path);
+        assert_eq!(code, Some(0), "Output: {}", output);
+        assert!(output.trim().is_empty(), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_file_name_disagrees_with_mark() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let chain_path = create_chain(&temp_dir, 2);
+        std::fs::rename(mark_path(&chain_path, 2), mark_path(&chain_path, 5))
+            .unwrap();
+
+        let (code, output) = validate_dir(&chain_path);
+        assert_eq!(code, Some(13), "Output: {}", output);
+        assert!(
+            output.contains("file is named for mark 5 but holds mark 2"),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_stored_fields_disagree_with_mark() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let chain_path = create_chain(&temp_dir, 2);
+        let path = mark_path(&chain_path, 1);
+        let mut json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap())
+                .unwrap();
+        json["bytewords"] = "🅟 ABLE ACID ALSO APEX".into();
+        std::fs::write(&path, serde_json::to_string_pretty(&json).unwrap())
+            .unwrap();
+
+        let (code, output) = validate_dir(&chain_path);
+        assert_eq!(code, Some(13), "Output: {}", output);
+        assert!(
+            output.contains("mark 1: stored `bytewords` does not match"),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_generator_disagrees_with_marks() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let chain_path = create_chain(&temp_dir, 2);
+        std::fs::remove_file(mark_path(&chain_path, 2)).unwrap();
+
+        let (code, output) = validate_dir(&chain_path);
+        assert_eq!(code, Some(13), "Output: {}", output);
+        assert!(
+            output.contains(
+                "generator's next sequence number is 3, but the highest mark is 1"
+            ),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_stray_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let chain_path = create_chain(&temp_dir, 1);
+        std::fs::write(chain_path.join("marks").join("notes.json"), "{}")
+            .unwrap();
+
+        let (code, output) = validate_dir(&chain_path);
+        assert_eq!(code, Some(13), "Output: {}", output);
+        assert!(
+            output.contains("notes.json: stray file"),
+            "Output: {}",
+            output
+        );
+    }
+}
+
+mod recursive {
+    use super::*;
+
+    fn create_chain(path: &std::path::Path, last: u32) {
+        provenance()
+            .arg("new")
+            .arg(path)
+            .args(["--date", "2023-06-20T12:00:00Z", "--quiet"])
+            .assert()
+            .success();
+        for i in 1..=last {
+            provenance()
+                .arg("next")
+                .arg(path)
+                .args(["--date", &format!("2023-06-{}T12:00:00Z", 20 + i)])
+                .arg("--quiet")
+                .assert()
+                .success();
+        }
+    }
+
+    /// Create a repository with two sound chains, one nested deeper than the
+    /// other, and one chain with a missing mark.
+    fn create_repository() -> TempDir {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let root = temp_dir.path();
+        create_chain(&root.join("alpha"), 2);
+        std::fs::create_dir(root.join("team")).unwrap();
+        create_chain(&root.join("team").join("beta"), 1);
+        create_chain(&root.join("gamma"), 3);
+        std::fs::remove_file(root.join("gamma/marks/mark-2.json")).unwrap();
+        std::fs::create_dir(root.join("empty")).unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_recursive_text() {
+        let temp_dir = create_repository();
+        let root = temp_dir.path().to_string_lossy().to_string();
+
+        let (code, output) = run_validate_with_args(
+            &["--recursive", &root, "--fail-on", "gap"],
+            &[],
+        );
+        assert_eq!(code, Some(5), "Output: {}", output);
+        assert!(
+            output.contains("ok   alpha (3 marks)"),
+            "Output: {}",
+            output
+        );
+        assert!(
+            output.contains("ok   team/beta (2 marks)"),
+            "Output: {}",
+            output
+        );
+        assert!(
+            output.contains("fail gamma (3 marks)"),
+            "Output: {}",
+            output
+        );
+        assert!(
+            output.contains(
+                "3 chain directories, 8 marks: 2 passed, 0 with warnings, 1 failed"
+            ),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_recursive_json() {
+        let temp_dir = create_repository();
+        let root = temp_dir.path().to_string_lossy().to_string();
+
+        let output = provenance()
+            .args(["validate", "--recursive", &root])
+            .args(["--allow", "gap", "--format", "json-compact"])
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("output is JSON");
+
+        let directories = json["directories"].as_array().unwrap();
+        let paths: Vec<&str> = directories
+            .iter()
+            .map(|dir| dir["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(paths, ["alpha", "gamma", "team/beta"]);
+        assert_eq!(directories[1]["status"], "warn");
+        assert_eq!(directories[1]["exitCode"], serde_json::Value::Null);
+        assert_eq!(json["totals"]["marks"], 8);
+        assert_eq!(json["totals"]["warned"], 1);
+    }
+
+    #[test]
+    fn test_recursive_without_chains() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let root = temp_dir.path().to_string_lossy().to_string();
+
+        let (code, output) =
+            run_validate_with_args(&["--recursive", &root], &[]);
+        assert_eq!(code, Some(1), "Output: {}", output);
+        assert!(
+            output.contains("No chain directories found"),
+            "Output: {}",
+            output
+        );
+    }
+}
+
+mod streaming {
+    use super::*;
+
+    fn create_chain(temp_dir: &TempDir, last: u32) -> std::path::PathBuf {
+        let chain_path = temp_dir.path().join("chain");
+        provenance()
+            .arg("new")
+            .arg(&chain_path)
+            .args(["--date", "2023-06-20T12:00:00Z", "--quiet"])
+            .assert()
+            .success();
+        for i in 1..=last {
+            provenance()
+                .arg("next")
+                .arg(&chain_path)
+                .args(["--date", &format!("2023-07-{:02}T12:00:00Z", i)])
+                .args(["--comment", &format!("Mark {}", i)])
+                .arg("--quiet")
+                .assert()
+                .success();
+        }
+        chain_path
+    }
+
+    #[test]
+    fn test_stream_valid_directory() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let chain_path = create_chain(&temp_dir, 11);
+
+        let (code, output) = run_validate_with_args(
+            &["--stream", "--dir", &chain_path.to_string_lossy()],
+            &[],
+        );
+        assert_eq!(code, Some(0), "Output: {}", output);
+        assert!(output.trim().is_empty(), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_stream_directory_with_gap() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let chain_path = create_chain(&temp_dir, 3);
+        std::fs::remove_file(chain_path.join("marks/mark-2.json")).unwrap();
+
+        let (code, output) = run_validate_with_args(
+            &["--stream", "--dir", &chain_path.to_string_lossy()],
+            &[],
+        );
+        assert_eq!(code, Some(5), "Output: {}", output);
+        assert!(
+            output.contains(", mark 3: mark 2 is missing"),
+            "Output: {}",
+            output
+        );
+        assert!(
+            output.contains("Total marks: 3, chains: 1, issues: 1"),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_stream_urs_agree_with_full_validation() {
+        let mut marks =
+            create_test_marks(4, ProvenanceMarkResolution::Low, "alice");
+        marks.remove(2);
+        marks.extend(create_test_marks(
+            2,
+            ProvenanceMarkResolution::Low,
+            "bob",
+        ));
+        let ur_strings = marks_to_ur_strings(&marks);
+
+        let (full_code, _) = run_validate_with_args(&[], &ur_strings);
+        let (stream_code, output) =
+            run_validate_with_args(&["--stream"], &ur_strings);
+        assert_eq!(stream_code, full_code, "Output: {}", output);
+        assert!(output.contains("gap: "), "Output: {}", output);
+        assert!(
+            output.contains("multiple-chains: marks belong to 2 chains"),
+            "Output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_stream_json_lines() {
+        let marks = create_test_marks(4, ProvenanceMarkResolution::Low, "test");
+        let ur_strings = marks_to_ur_strings(&marks[1..]);
+
+        let output = provenance()
+            .args(["validate", "--stream", "--format", "json-compact"])
+            .args(&ur_strings)
+            .output()
+            .expect("Failed to execute command");
+        assert_eq!(output.status.code(), Some(6));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let issues: Vec<serde_json::Value> = stdout
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("line is JSON"))
+            .collect();
+        assert_eq!(issues.len(), 1, "Output: {}", stdout);
+        assert_eq!(issues[0]["class"], "missing-genesis");
+    }
+
+    #[test]
+    fn test_stream_newest_first_is_not_a_fork() {
+        let mut marks =
+            create_test_marks(4, ProvenanceMarkResolution::Low, "test");
+        marks.reverse();
+        let (code, output) =
+            run_validate_with_args(&["--stream"], &marks_to_ur_strings(&marks));
+        assert_eq!(code, Some(1), "Output: {}", output);
+        assert!(output.contains("in sequence order"), "Output: {}", output);
+        assert!(!output.contains("fork"), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_stream_rejects_report_formats() {
+        let marks = create_test_marks(2, ProvenanceMarkResolution::Low, "test");
+        let (code, output) = run_validate_with_args(
+            &["--stream", "--format", "junit"],
+            &marks_to_ur_strings(&marks),
+        );
+        assert_eq!(code, Some(1), "Output: {}", output);
+    }
+
+    #[test]
+    fn test_print_json_matches_mark_files() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let chain_path = create_chain(&temp_dir, 3);
+
+        let output = provenance()
+            .arg("print")
+            .arg(&chain_path)
+            .args(["--format", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        let mark_infos: Vec<provenance_mark::ProvenanceMarkInfo> = (0..=3)
+            .map(|seq| {
+                let path = chain_path.join(format!("marks/mark-{}.json", seq));
+                serde_json::from_str(&std::fs::read_to_string(path).unwrap())
+                    .unwrap()
+            })
+            .collect();
+        let expected = serde_json::to_string_pretty(&mark_infos).unwrap();
+        assert_eq!(String::from_utf8_lossy(&output), format!("{}\n", expected));
+    }
+}